@@ -0,0 +1,61 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Machine-readable reason a match could not be produced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchError {
+    /// The hOCR content or the search string was empty
+    EmptyInput,
+    /// The search string contained only whitespace, so there were no words to match
+    BlankSearch,
+    /// The hOCR content contained no usable `ocrx_word` boxes
+    NoWordBoxes,
+    /// No window of words matched the search string
+    NoMatch,
+    /// The best match scored below the requested similarity
+    BelowThreshold,
+}
+
+impl MatchError {
+    /// Stable error code exposed to JavaScript
+    pub fn code(&self) -> &'static str {
+        match self {
+            MatchError::EmptyInput => "EmptyInput",
+            MatchError::BlankSearch => "BlankSearch",
+            MatchError::NoWordBoxes => "NoWordBoxes",
+            MatchError::NoMatch => "NoMatch",
+            MatchError::BelowThreshold => "BelowThreshold",
+        }
+    }
+    
+    /// Human-readable description of the error
+    pub fn message(&self) -> &'static str {
+        match self {
+            MatchError::EmptyInput => "hOCR content or search string is empty",
+            MatchError::BlankSearch => "search string contains only whitespace",
+            MatchError::NoWordBoxes => "no word bounding boxes found in hOCR content",
+            MatchError::NoMatch => "no matching word sequence found",
+            MatchError::BelowThreshold => "best match is below the similarity threshold",
+        }
+    }
+}
+
+impl fmt::Display for MatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code(), self.message())
+    }
+}
+
+impl std::error::Error for MatchError {}
+
+/// Thrown into JavaScript as `{code, message}`
+impl From<MatchError> for JsValue {
+    fn from(error: MatchError) -> JsValue {
+        let js_error = js_sys::Object::new();
+        js_sys::Reflect::set(&js_error, &"code".into(), &error.code().into()).unwrap();
+        js_sys::Reflect::set(&js_error, &"message".into(), &error.message().into()).unwrap();
+        
+        js_error.into()
+    }
+}
\ No newline at end of file
@@ -1,6 +1,10 @@
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
 use regex::Regex;
+use std::collections::HashMap;
+use crate::errors::MatchError;
+use crate::string_matching::{classify_match_confidence, find_closest_match, grapheme_clusters, select_non_overlapping_windows, MatchResult};
+use crate::utils::log;
 
 #[wasm_bindgen]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +23,84 @@ struct WordBox {
     y1: f64,
     x2: f64,
     y2: f64,
+    /// Index of the enclosing `ocr_line` in document order
+    line_index: usize,
+    /// Word is a redaction placeholder (all block characters or an `ocr_redacted` class)
+    redacted: bool,
+    /// Word is a superscript (`<sup>` markup, or small and raised above its line)
+    superscript: bool,
+    /// Foreground color from an `fcolor`/`x_fcolor` title field or a `color` style,
+    /// as written; None means the default (black)
+    color: Option<String>,
+    /// Byte range of the whole `ocrx_word` element (opening tag to `</span>`) in the hOCR
+    source_span: (usize, usize),
+}
+
+/// Page size and orientation read from an `ocr_page` title
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PageDimensions {
+    pub width: f64,
+    pub height: f64,
+    /// Rotation in degrees from the `rotate` (or `textangle`) field, 0 when absent
+    pub rotation: f64,
+}
+
+/// Options controlling how a match in hOCR is turned into geometry
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExtractOptions {
+    /// Leave trailing punctuation-only words (".", ",") out of the matched box
+    pub trim_trailing_punct: bool,
+    /// Drop redacted words before matching so they don't break a phrase
+    pub skip_redacted: bool,
+    /// Clamp the matched box to the `ocr_page` dimensions, when the page declares them
+    pub clamp_to_page: bool,
+    /// Drop superscript words (e.g. footnote markers) before matching
+    pub exclude_superscripts: bool,
+    /// Read a two-column page without `ocr_carea` tags column by column: words whose
+    /// x-midpoint is left of the page center come before those right of it
+    pub two_column: bool,
+    /// Reject a match whose union box is wider than this many pixels, a sign that
+    /// the words were mapped to scattered boxes
+    pub max_span_width: Option<f64>,
+}
+
+#[wasm_bindgen]
+impl ExtractOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> ExtractOptions {
+        ExtractOptions::default()
+    }
+}
+
+/// Word boxes covered by a sliding-window match, with the window's score
+#[derive(Debug, Clone)]
+struct WordBoxMatch {
+    word_boxes: Vec<WordBox>,
+    similarity: f64,
+    /// Index of the first matched box in the word boxes searched
+    first_box: usize,
+}
+
+impl WordBoxMatch {
+    /// Collect the boxes at `indices` (ascending); None when there are none
+    fn from_indices(word_boxes: &[WordBox], indices: &[usize], similarity: f64) -> Option<WordBoxMatch> {
+        Some(WordBoxMatch {
+            first_box: *indices.first()?,
+            word_boxes: indices.iter().map(|&i| word_boxes[i].clone()).collect(),
+            similarity,
+        })
+    }
+}
+
+/// Match located in hOCR: union box of the matched words plus match quality
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HocrMatchResult {
+    pub bbox: BoundingBox,
+    pub similarity: f64,
+    pub matched_text: String,
+    pub word_count: usize,
 }
 
 #[wasm_bindgen]
@@ -29,468 +111,3416 @@ impl BoundingBox {
     }
 }
 
-/// Debug function to return raw hOCR content (first 2000 chars)
-#[wasm_bindgen]
-pub fn debug_get_raw_hocr(hocr_content: &str) -> String {
-    hocr_content.chars().take(2000).collect()
+/// An opening tag found in hOCR content, with its attributes parsed by name
+#[derive(Debug, Clone)]
+struct HocrElement {
+    /// Byte offset of the opening `<`
+    start: usize,
+    /// Byte offset just past the `>`
+    end: usize,
+    attributes: HashMap<String, String>,
+    self_closing: bool,
 }
 
-/// Extract embedded text from hOCR content
-/// Ported from Python extract_text.py logic
-#[wasm_bindgen]
-pub fn extract_embedded_text_from_hocr(hocr_content: &str) -> String {
-    // This is a simplified version - in production, we'd use a proper HTML parser
-    // For now, we'll extract text and preserve LINE markers similar to the Python version
+impl HocrElement {
+    fn has_class(&self, class_name: &str) -> bool {
+        self.attributes.get("class")
+            .is_some_and(|classes| classes.split_whitespace().any(|c| c == class_name))
+    }
     
-    let mut embedded_text = Vec::new();
+    fn title_fields(&self) -> HashMap<String, String> {
+        self.attributes.get("title")
+            .map(|title| parse_title_fields(title))
+            .unwrap_or_default()
+    }
     
-    // Find all paragraph sections
-    let par_regex = Regex::new(r#"<p[^>]*class=['"]ocr_par['"][^>]*>"#).unwrap();
-    let line_regex = Regex::new(r#"<span[^>]*class=['"]ocr_line['"][^>]*title=['"]([^'"]*bbox\s+(\d+)\s+(\d+)\s+(\d+)\s+(\d+))[^'"]*['"][^>]*>"#).unwrap();
-    let word_regex = Regex::new(r#"<span[^>]*class=['"]ocrx_word['"][^>]*>([^<]*)</span>"#).unwrap();
+    /// Raw content between this tag and the next closing `</tag_name>`
+    fn inner_html<'a>(&self, content: &'a str, tag_name: &str) -> &'a str {
+        if self.self_closing {
+            return "";
+        }
+        let rest = &content[self.end..];
+        let closing = format!("</{}>", tag_name);
+        rest.find(&closing).map_or(rest, |end| &rest[..end])
+    }
     
-    // Process each paragraph
-    for par_match in par_regex.find_iter(hocr_content) {
-        embedded_text.push("[[PARAGRAPH]]".to_string());
-        
-        // Find the content after this paragraph tag
-        let par_end = par_match.end();
-        let par_content = &hocr_content[par_end..];
+    /// Byte offset just past the closing `</tag_name>` found by `inner_html`, or past the
+    /// opening tag when there is none
+    fn outer_end(&self, content: &str, tag_name: &str) -> usize {
+        if self.self_closing {
+            return self.end;
+        }
+        let closing = format!("</{}>", tag_name);
+        content[self.end..].find(&closing).map_or(self.end, |end| self.end + end + closing.len())
+    }
+}
+
+/// Parse the attributes of a tag body (the text between the tag name and `>`)
+/// Attribute order does not matter and single, double or mixed quoting is accepted
+/// Unquoted values end at whitespace, except `title`, whose unquoted value
+/// (`title=bbox 1 2 3 4`) runs until the next `name=` token
+fn parse_tag_attributes(tag_body: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut rest = tag_body.trim_start();
+    
+    while !rest.is_empty() {
+        let name_end = rest.find(|c: char| c.is_whitespace() || c == '=' || c == '/').unwrap_or(rest.len());
+        let name = rest[..name_end].to_lowercase();
+        rest = rest[name_end..].trim_start();
         
-        // Find the end of this paragraph
-        if let Some(end_p_pos) = par_content.find("</p>") {
-            let par_content = &par_content[..end_p_pos];
-            
-            // Extract lines within this paragraph
-            for line_cap in line_regex.captures_iter(par_content) {
-                if let (Some(x1), Some(y1), Some(x2), Some(y2)) = (
-                    line_cap.get(2),
-                    line_cap.get(3), 
-                    line_cap.get(4),
-                    line_cap.get(5)
-                ) {
-                    let line_marker = format!("[[LINE {} {} {} {}]]", 
-                        x1.as_str(), y1.as_str(), x2.as_str(), y2.as_str());
-                    embedded_text.push(line_marker);
+        let mut value = String::new();
+        if let Some(after_eq) = rest.strip_prefix('=') {
+            let after_eq = after_eq.trim_start();
+            match after_eq.chars().next() {
+                Some(quote @ ('\'' | '"')) => {
+                    let body = &after_eq[1..];
+                    let value_end = body.find(quote).unwrap_or(body.len());
+                    value = body[..value_end].to_string();
+                    rest = body.get(value_end + 1..).unwrap_or("");
                 }
-            }
-            
-            // Extract all words within this paragraph
-            for word_cap in word_regex.captures_iter(par_content) {
-                if let Some(word_text) = word_cap.get(1) {
-                    let word = word_text.as_str().trim();
-                    if !word.is_empty() {
-                        embedded_text.push(word.to_string());
+                _ => {
+                    let mut value_end = after_eq.find(char::is_whitespace).unwrap_or(after_eq.len());
+                    if name == "title" {
+                        value_end = unquoted_title_end(after_eq, value_end);
                     }
+                    value = after_eq[..value_end].to_string();
+                    rest = &after_eq[value_end..];
                 }
             }
+        } else if name.is_empty() {
+            // Stray character such as the `/` of a self-closing tag
+            rest = &rest[1..];
+        }
+        
+        if !name.is_empty() {
+            attributes.insert(name, value);
         }
+        rest = rest.trim_start();
     }
     
-    embedded_text.join(" ")
+    attributes
 }
 
-/// Extract bounding box coordinates using word-level coordinates (improved version)
-/// This version uses the original hOCR content to find word-level bounding boxes
-#[wasm_bindgen]
-pub fn extract_bounding_box_from_hocr(hocr_content: &str, closest_match_string: &str) -> Option<BoundingBox> {
-    if hocr_content.is_empty() || closest_match_string.is_empty() {
-        web_sys::console::log_1(&"Empty input to extract_bounding_box_from_hocr".into());
-        return None;
-    }
-    
-    web_sys::console::log_1(&format!("WASM: Extracting bbox for '{}'", closest_match_string).into());
-    web_sys::console::log_1(&format!("WASM: hOCR preview: {}", &hocr_content.chars().take(100).collect::<String>()).into());
+/// End of an unquoted multi-token title value whose first token ends at `first_end`:
+/// following tokens belong to it until one is another attribute (`name=...`) or `/`
+fn unquoted_title_end(value: &str, first_end: usize) -> usize {
+    let mut end = first_end;
     
-    // Extract word-level bounding boxes from hOCR
-    let word_boxes = extract_word_bounding_boxes(hocr_content);
-    
-    web_sys::console::log_1(&format!("WASM: Found {} word boxes", word_boxes.len()).into());
-    if word_boxes.is_empty() {
-        web_sys::console::log_1(&"WASM: No word boxes found!".into());
-        return None;
+    loop {
+        let rest = &value[end..];
+        let token_start = end + (rest.len() - rest.trim_start().len());
+        let token_len = value[token_start..].find(char::is_whitespace).unwrap_or(value.len() - token_start);
+        let token = &value[token_start..token_start + token_len];
+        if token.is_empty() || token == "/" || token.contains('=') {
+            return end;
+        }
+        end = token_start + token_len;
     }
+}
+
+/// Whether `content` opens a `<tag_name>` element, without compiling a tag regex
+fn has_opening_tag(content: &str, tag_name: &str) -> bool {
+    let prefix = format!("<{}", tag_name);
+    content.match_indices(&prefix).any(|(i, _)| {
+        content[i + prefix.len()..].chars().next().is_some_and(|c| c == '>' || c == '/' || c.is_whitespace())
+    })
+}
+
+/// Find every opening `<tag_name ...>` tag in document order
+fn find_elements(content: &str, tag_name: &str) -> Vec<HocrElement> {
+    let tag_regex = Regex::new(&format!(r"<{}\b([^>]*)>", regex::escape(tag_name))).unwrap();
     
-    // Use JS/Python sliding window approach directly on hOCR text
-    web_sys::console::log_1(&format!("WASM: Using JS/Python sliding window approach for '{}'", closest_match_string).into());
-    
-    // Extract clean text from word boxes (like embedded text with markers)
-    let embedded_text = create_embedded_text_from_word_boxes(&word_boxes);
-    web_sys::console::log_1(&format!("WASM: Created embedded text: {}", &embedded_text.chars().take(200).collect::<String>()).into());
-    
-    // Use the same algorithm as JS implementation
-    let matching_word_boxes = find_js_style_match(&embedded_text, closest_match_string, &word_boxes);
+    tag_regex.captures_iter(content)
+        .filter_map(|caps| {
+            let tag = caps.get(0)?;
+            let body = caps.get(1)?.as_str();
+            let self_closing = body.trim_end().ends_with('/');
+            
+            Some(HocrElement {
+                start: tag.start(),
+                end: tag.end(),
+                attributes: parse_tag_attributes(body),
+                self_closing,
+            })
+        })
+        .collect()
+}
+
+/// Parse an hOCR title attribute into its semicolon-separated fields
+/// e.g. `"ppageno 2; rotate 90; bbox 0 0 2550 3300"` maps `bbox` to `"0 0 2550 3300"`
+fn parse_title_fields(title: &str) -> HashMap<String, String> {
+    let mut fields = HashMap::new();
     
-    web_sys::console::log_1(&format!("WASM: Found {} matching boxes", matching_word_boxes.len()).into());
-    if matching_word_boxes.is_empty() {
-        web_sys::console::log_1(&"WASM: No matching word sequence found!".into());
-        return None;
+    for field in title.split(';') {
+        let field = field.trim();
+        if field.is_empty() {
+            continue;
+        }
+        let (key, value) = match field.split_once(char::is_whitespace) {
+            Some((key, value)) => (key, value.trim()),
+            None => (field, ""),
+        };
+        fields.insert(key.to_string(), value.to_string());
     }
     
-    // Calculate bounding box from matching words
-    let result = calculate_bounding_box_from_words(&matching_word_boxes);
-    if let Some(ref bbox) = result {
-        web_sys::console::log_1(&format!("WASM: Final bbox: [{}, {}, {}, {}]", bbox.x1, bbox.y1, bbox.x2, bbox.y2).into());
-    }
-    result
+    fields
 }
 
-/// Extract bounding box coordinates for a matched string (legacy version using embedded text)
-/// Ported from Python extract_box.py logic - using word-level matching like Python
-#[wasm_bindgen] 
-pub fn extract_bounding_box(embedded_text: &str, closest_match_string: &str) -> Option<BoundingBox> {
-    if embedded_text.is_empty() || closest_match_string.is_empty() {
-        return None;
-    }
-    
-    // Split into words for matching (following Python approach)
-    let text_words: Vec<&str> = embedded_text.split_whitespace().collect();
-    let search_words: Vec<&str> = closest_match_string.split_whitespace().collect();
+/// Read the `bbox x1 y1 x2 y2` field from parsed title fields
+fn parse_bbox_field(fields: &HashMap<String, String>) -> Option<(f64, f64, f64, f64)> {
+    let values: Vec<f64> = fields.get("bbox")?
+        .split_whitespace()
+        .map(|v| v.parse::<f64>())
+        .collect::<Result<_, _>>()
+        .ok()?;
     
-    if search_words.is_empty() {
-        return None;
+    match values[..] {
+        [x1, y1, x2, y2] => Some((x1, y1, x2, y2)),
+        _ => None,
     }
+}
+
+/// Decode the HTML character references that commonly appear in hOCR word text
+fn decode_html_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
     
-    let search_length = search_words.len();
-    let mut best_similarity = 0.0;
-    let mut best_start_index = 0;
-    let mut best_end_index = 0;
-    
-    // Search from end to beginning as in the original Python code
-    for end_index in (search_length..=text_words.len()).rev() {
-        let start_index = end_index - search_length;
-        let window_words = &text_words[start_index..end_index];
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
         
-        // Remove hOCR markers for comparison
-        let cleaned_window: Vec<String> = window_words.iter()
-            .filter(|word| !word.starts_with("[[") || !word.ends_with("]]"))
-            .map(|word| word.to_string())
-            .collect();
+        let entity = rest.find(';').map(|end| (&rest[1..end], end));
+        let replacement = entity.and_then(|(name, _)| match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => {
+                let code = if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                    u32::from_str_radix(hex, 16).ok()
+                } else {
+                    name.strip_prefix('#').and_then(|dec| dec.parse::<u32>().ok())
+                };
+                code.and_then(char::from_u32)
+            }
+        });
         
-        if cleaned_window.len() >= search_length {
-            let window_text = cleaned_window.join(" ");
-            let similarity = calculate_string_similarity(&window_text, closest_match_string);
-            
-            if similarity > best_similarity {
-                best_similarity = similarity;
-                best_start_index = start_index;
-                best_end_index = end_index;
-                
-                if best_similarity > 0.85 {
-                    break;
-                }
+        match (replacement, entity) {
+            (Some(c), Some((_, end))) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
             }
         }
     }
     
-    // Convert word indices back to character indices in the original text
-    let mut char_start_index = 0;
-    let mut char_end_index = embedded_text.len();
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Strip nested HTML tags (like BeautifulSoup's get_text()), decode entities and trim
+/// This is the display form stored in `WordBox.text`
+fn strip_word_markup(raw: &str) -> String {
+    let mut text = String::with_capacity(raw.len());
+    let mut rest = raw;
     
-    if best_start_index > 0 {
-        let words_before: Vec<&str> = text_words[..best_start_index].to_vec();
-        char_start_index = words_before.join(" ").len();
-        if char_start_index > 0 { char_start_index += 1; } // Add space
+    while let Some(open) = rest.find('<') {
+        text.push_str(&rest[..open]);
+        match rest[open + 1..].find('>') {
+            Some(len) if len > 0 => rest = &rest[open + len + 2..],
+            _ => {
+                text.push('<');
+                rest = &rest[open + 1..];
+            }
+        }
     }
+    text.push_str(rest);
     
-    if best_end_index < text_words.len() {
-        let words_up_to_end: Vec<&str> = text_words[..best_end_index].to_vec();
-        char_end_index = words_up_to_end.join(" ").len();
+    decode_html_entities(&text).trim().to_string()
+}
+
+/// Clean a single word exactly the way the matcher does before comparison:
+/// strip HTML tags, decode entities, trim and case-fold (see `fold_case`)
+#[wasm_bindgen]
+pub fn clean_word(raw: &str) -> String {
+    fold_case(&strip_word_markup(raw))
+}
+
+/// Locale-insensitive case fold: lowercase, plus `ß`/`ẞ` to "ss", final `ς` to `σ`, and
+/// Turkish `İ`/`ı` to plain `i` so dotted and dotless spellings compare equal
+fn fold_case(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            'ß' | 'ẞ' => folded.push_str("ss"),
+            'ς' => folded.push('σ'),
+            'İ' | 'ı' => folded.push('i'),
+            _ => folded.extend(c.to_lowercase()),
+        }
     }
+    folded
+}
+
+/// Extract the dimensions and rotation of the first `ocr_page` in hOCR content
+#[wasm_bindgen]
+pub fn extract_page_dimensions(hocr_content: &str) -> Option<PageDimensions> {
+    let page = find_elements(hocr_content, "div").into_iter().find(|e| e.has_class("ocr_page"))?;
     
-    // Extract LINE markers to calculate bounding box coordinates
-    let before_match = &embedded_text[..char_start_index];
-    let match_section = &embedded_text[char_start_index..char_end_index];
-    
-    // Find the last LINE marker before the match
-    let line_regex = Regex::new(r"\[\[LINE (\d+) (\d+) (\d+) (\d+)\]\]").unwrap();
+    let fields = page.title_fields();
+    let (x1, y1, x2, y2) = parse_bbox_field(&fields)?;
+    let rotation = fields.get("rotate")
+        .or_else(|| fields.get("textangle"))
+        .and_then(|v| v.parse::<f64>().ok())
+        .unwrap_or(0.0);
     
-    let mut x1 = 0.0;
-    let mut y1 = 0.0;
-    let mut x2 = 0.0;
-    let mut y2 = 0.0;
+    Some(PageDimensions {
+        width: x2 - x1,
+        height: y2 - y1,
+        rotation,
+    })
+}
+
+/// Split concatenated hOCR into one slice per `ocr_page` div, in document order
+/// Each slice runs from its page tag to the matching `</div>` (nested divs are
+/// balanced), or up to the next page when the markup is unbalanced; content
+/// without page divs is returned whole as a single page
+fn split_pages(hocr_content: &str) -> Vec<&str> {
+    let divs = find_elements(hocr_content, "div");
+    let pages: Vec<&HocrElement> = divs.iter().filter(|e| e.has_class("ocr_page")).collect();
     
-    // Get starting coordinates from the line before the match
-    if let Some(caps) = line_regex.captures_iter(before_match).last() {
-        x1 = caps[1].parse().unwrap_or(0.0);
-        y1 = caps[2].parse().unwrap_or(0.0);
+    if pages.is_empty() {
+        return vec![hocr_content];
     }
     
-    // Get ending coordinates from lines within the match
-    // Following Python logic: find max x2 and final y2
-    let mut max_x2 = x1; // Initialize with starting x in case no matches
-    for caps in line_regex.captures_iter(match_section) {
-        let current_x2: f64 = caps[3].parse().unwrap_or(0.0);
-        let current_y2: f64 = caps[4].parse().unwrap_or(0.0);
-        
-        // Find maximum x2 (rightmost coordinate) across all lines
-        if current_x2 > max_x2 {
-            max_x2 = current_x2;
-        }
-        // Always update y2 to the last line's bottom coordinate
-        y2 = current_y2;
+    pages.iter()
+        .enumerate()
+        .map(|(i, page)| {
+            let limit = pages.get(i + 1).map_or(hocr_content.len(), |next| next.start);
+            let end = balanced_element_end(hocr_content, page, "div").map_or(limit, |end| end.min(limit));
+            &hocr_content[page.start..end]
+        })
+        .collect()
+}
+
+/// Byte offset just past the closing tag that balances `element`, if there is one
+fn balanced_element_end(content: &str, element: &HocrElement, tag_name: &str) -> Option<usize> {
+    if element.self_closing {
+        return Some(element.end);
     }
     
-    x2 = max_x2;
-    
-    // Return None if no valid coordinates found
-    if x1 == 0.0 && y1 == 0.0 && x2 == 0.0 && y2 == 0.0 {
-        return None;
+    let tag_regex = Regex::new(&format!(r"<(/?){}\b[^>]*?(/?)>", regex::escape(tag_name))).unwrap();
+    let mut depth = 1;
+    for caps in tag_regex.captures_iter(&content[element.end..]) {
+        match (&caps[1], &caps[2]) {
+            ("/", _) => depth -= 1,
+            (_, "/") => {}
+            _ => depth += 1,
+        }
+        if depth == 0 {
+            return Some(element.end + caps.get(0)?.end());
+        }
     }
     
-    Some(BoundingBox::new(x1, y1, x2, y2))
+    None
 }
 
-/// Extract all word bounding boxes from hOCR content
-fn extract_word_bounding_boxes(hocr_content: &str) -> Vec<WordBox> {
-    let mut word_boxes = Vec::new();
+/// Each `ocr_page` of concatenated hOCR as its own string, so pages can be processed separately
+/// Content without page divs comes back as a single entry
+#[wasm_bindgen]
+pub fn split_hocr_pages(hocr_content: &str) -> js_sys::Array {
+    split_pages(hocr_content)
+        .into_iter()
+        .map(JsValue::from)
+        .collect()
+}
+
+/// Debug function to return raw hOCR content (first 2000 chars)
+#[wasm_bindgen]
+pub fn debug_get_raw_hocr(hocr_content: &str) -> String {
+    hocr_content.chars().take(2000).collect()
+}
+
+/// Extract embedded text from hOCR content
+/// Ported from Python extract_text.py logic
+#[wasm_bindgen]
+pub fn extract_embedded_text_from_hocr(hocr_content: &str) -> String {
+    // This is a simplified version - in production, we'd use a proper HTML parser
+    // For now, we'll extract text and preserve LINE markers similar to the Python version
     
-    // Use regex to find all word elements with bounding boxes, including nested HTML tags
-    let word_regex = Regex::new(r#"<span[^>]*class=['"]ocrx_word['"][^>]*title=['"]([^'"]*bbox\s+(\d+)\s+(\d+)\s+(\d+)\s+(\d+))[^'"]*['"][^>]*>(.*?)</span>"#).unwrap();
-    
-    for caps in word_regex.captures_iter(hocr_content) {
-        if let (Some(x1), Some(y1), Some(x2), Some(y2), Some(text)) = (
-            caps.get(2),
-            caps.get(3),
-            caps.get(4),
-            caps.get(5),
-            caps.get(6)
-        ) {
-            let x1_val: f64 = x1.as_str().parse().unwrap_or(0.0);
-            let y1_val: f64 = y1.as_str().parse().unwrap_or(0.0);
-            let x2_val: f64 = x2.as_str().parse().unwrap_or(0.0);
-            let y2_val: f64 = y2.as_str().parse().unwrap_or(0.0);
-            let raw_text = text.as_str();
-            
-            // Strip HTML tags using regex (like BeautifulSoup's get_text())
-            let html_tag_regex = Regex::new(r"<[^>]+>").unwrap();
-            let clean_text_str = html_tag_regex.replace_all(raw_text, "").trim().to_string();
-            
-            // Create clean version for matching (keep alphanumeric and spaces for debugging)
-            let clean_text_for_matching = clean_text_str.to_lowercase();
-            
-            if !clean_text_str.is_empty() && x1_val >= 0.0 && y1_val >= 0.0 && x2_val > x1_val && y2_val > y1_val {
-                word_boxes.push(WordBox {
-                    text: clean_text_str.clone(), // Store the clean text without HTML tags
-                    clean_text: clean_text_for_matching,
-                    x1: x1_val,
-                    y1: y1_val,
-                    x2: x2_val,
-                    y2: y2_val,
-                });
-            }
+    let mut embedded_text = Vec::new();
+    
+    // Process each paragraph
+    let paragraphs = paragraph_bodies(hocr_content);
+    for body in &paragraphs {
+        embedded_text.push("[[PARAGRAPH]]".to_string());
+        if let Some(body) = body {
+            push_lines_and_words(body, &mut embedded_text);
         }
     }
     
-    word_boxes
+    // The whole-content fallback yields nothing at all when it holds no words either
+    if embedded_text.len() == 1 && paragraphs == [Some(hocr_content)] {
+        embedded_text.clear();
+    }
+    
+    embedded_text.join(" ")
 }
 
-/// Create embedded text from word boxes (like JS embedded text with LINE markers)
-fn create_embedded_text_from_word_boxes(word_boxes: &[WordBox]) -> String {
-    let mut embedded_text = String::from("[[PARAGRAPH]] ");
+/// Body of each `ocr_par` paragraph (None when it is never closed); some engines put
+/// `ocr_line` directly under `ocr_page`, so without paragraphs the whole content is one
+fn paragraph_bodies(hocr_content: &str) -> Vec<Option<&str>> {
+    let paragraphs: Vec<Option<&str>> = find_elements(hocr_content, "p")
+        .iter()
+        .filter(|e| e.has_class("ocr_par"))
+        .map(|par| {
+            let par_content = &hocr_content[par.end..];
+            par_content.find("</p>").map(|end_p_pos| &par_content[..end_p_pos])
+        })
+        .collect();
     
-    // Add a LINE marker (using first and last word coordinates)
-    if !word_boxes.is_empty() {
-        let first_word = &word_boxes[0];
-        let last_word = &word_boxes[word_boxes.len() - 1];
-        embedded_text.push_str(&format!("[[LINE {} {} {} {}]] ", 
-            first_word.x1 as i32, first_word.y1 as i32, 
-            last_word.x2 as i32, last_word.y2 as i32));
+    if paragraphs.is_empty() {
+        vec![Some(hocr_content)]
+    } else {
+        paragraphs
     }
+}
+
+/// Every word `extract_embedded_text_from_hocr` emits, in order, with its box (None when
+/// the span has no usable bbox)
+fn embedded_words_with_boxes(hocr_content: &str) -> Vec<(String, Option<BoundingBox>)> {
+    paragraph_bodies(hocr_content)
+        .into_iter()
+        .flatten()
+        .flat_map(|body| {
+            find_elements(body, "span")
+                .into_iter()
+                .filter(|e| e.has_class("ocrx_word"))
+                .map(move |span| {
+                    let bbox = parse_bbox_field(&span.title_fields())
+                        .map(|(x1, y1, x2, y2)| BoundingBox::new(x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2)));
+                    (strip_word_markup(span.inner_html(body, "span")), bbox)
+                })
+        })
+        .filter(|(word, _)| !word.is_empty())
+        .collect()
+}
+
+/// Append the LINE markers and then the words found in a block of hOCR
+fn push_lines_and_words(content: &str, embedded_text: &mut Vec<String>) {
+    let spans = find_elements(content, "span");
     
-    // Add all the word text
-    for word_box in word_boxes {
-        embedded_text.push_str(&word_box.text);
-        embedded_text.push(' ');
+    // Extract lines within this block
+    for line in spans.iter().filter(|e| e.has_class("ocr_line")) {
+        if let Some((x1, y1, x2, y2)) = parse_bbox_field(&line.title_fields()) {
+            let line_marker = format!("[[LINE {} {} {} {}]]", x1, y1, x2, y2);
+            embedded_text.push(line_marker);
+        }
     }
     
-    embedded_text
+    // Extract all words within this block
+    for word_span in spans.iter().filter(|e| e.has_class("ocrx_word")) {
+        let word = strip_word_markup(word_span.inner_html(content, "span"));
+        if !word.is_empty() {
+            embedded_text.push(word);
+        }
+    }
 }
 
-/// Exact copy of JS TextMatcher.findClosestMatch algorithm
-fn find_js_style_match(embedded_text: &str, search_string: &str, word_boxes: &[WordBox]) -> Vec<WordBox> {
-    web_sys::console::log_1(&format!("WASM: JS-style matching '{}' in embedded text", search_string).into());
+/// Locate a search string in hOCR content and return the matched word boxes
+fn match_word_boxes(hocr_content: &str, closest_match_string: &str) -> Option<WordBoxMatch> {
+    try_match_word_boxes(hocr_content, closest_match_string).ok()
+}
+
+/// Text and box of each matched word, with the match similarity
+pub fn matched_word_regions(hocr_content: &str, search_string: &str) -> Option<(Vec<(String, BoundingBox)>, f64)> {
+    let word_match = match_word_boxes(hocr_content, search_string)?;
+    let regions = word_match.word_boxes.iter()
+        .map(|w| (w.text.clone(), BoundingBox::new(w.x1, w.y1, w.x2, w.y2)))
+        .collect();
     
-    if embedded_text.is_empty() || search_string.is_empty() {
-        return Vec::new();
+    Some((regions, word_match.similarity))
+}
+
+/// Locate a search string in hOCR content, reporting why when nothing is found
+fn try_match_word_boxes(hocr_content: &str, closest_match_string: &str) -> Result<WordBoxMatch, MatchError> {
+    try_match_word_boxes_with_options(hocr_content, closest_match_string, &ExtractOptions::default())
+}
+
+/// `try_match_word_boxes` with the word-filtering extraction options applied
+fn try_match_word_boxes_with_options(
+    hocr_content: &str,
+    closest_match_string: &str,
+    options: &ExtractOptions,
+) -> Result<WordBoxMatch, MatchError> {
+    if hocr_content.is_empty() || closest_match_string.is_empty() {
+        log!("Empty input to extract_bounding_box_from_hocr");
+        return Err(MatchError::EmptyInput);
+    }
+    if closest_match_string.trim().is_empty() {
+        log!("Whitespace-only search string");
+        return Err(MatchError::BlankSearch);
     }
     
-    // Clean the embedded text by removing hOCR markers (exact JS logic)
-    let regex = Regex::new(r"\[\[.*?\]\] ").unwrap();
-    let cleaned_text = regex.replace_all(embedded_text, "").to_string();
-    let cleaned_words: Vec<&str> = cleaned_text.split_whitespace().filter(|w| !w.is_empty()).collect();
-    let search_words: Vec<&str> = search_string.split_whitespace().filter(|w| !w.is_empty()).collect();
-    
-    web_sys::console::log_1(&format!("WASM: Cleaned text has {} words", cleaned_words.len()).into());
-    web_sys::console::log_1(&format!("WASM: Search has {} words", search_words.len()).into());
-    web_sys::console::log_1(&format!("WASM: Search words: {:?}", search_words).into());
+    log!("WASM: Extracting bbox for '{}'", closest_match_string);
+    log!("WASM: hOCR preview: {}", &hocr_content.chars().take(100).collect::<String>());
     
-    if search_words.is_empty() {
-        return Vec::new();
+    // Extract word-level bounding boxes from hOCR
+    let mut word_boxes = extract_word_bounding_boxes(hocr_content);
+    if options.skip_redacted {
+        word_boxes.retain(|w| !w.redacted);
+    }
+    if options.exclude_superscripts {
+        word_boxes.retain(|w| !w.superscript);
+    }
+    if options.two_column {
+        let page_width = extract_page_dimensions(hocr_content).map(|page| page.width);
+        word_boxes = order_two_columns(word_boxes, page_width);
     }
     
-    let window_size = search_words.len();
-    let mut best_cleaned_start_index = 0;
-    let mut best_similarity = 0.0;
+    log!("WASM: Found {} word boxes", word_boxes.len());
+    if word_boxes.is_empty() {
+        log!("WASM: No word boxes found!");
+        return Err(MatchError::NoWordBoxes);
+    }
     
-    // Sliding window approach (exact JS logic)
-    if window_size <= cleaned_words.len() {
-        for i in 0..=(cleaned_words.len() - window_size) {
-            let window_words = &cleaned_words[i..i + window_size];
-            
-            // Calculate sequence similarity (exact JS logic)
-            let similarity = js_sequence_similarity(window_words, &search_words);
-            
-            if similarity > best_similarity {
-                best_similarity = similarity;
-                best_cleaned_start_index = i;
-                
-                // Early exit for perfect match (exact JS logic)
-                if similarity >= 0.95 {
-                    web_sys::console::log_1(&format!("WASM: Perfect match found at position {}", i).into());
-                    break;
-                }
+    // Use JS/Python sliding window approach directly on hOCR text
+    log!("WASM: Using JS/Python sliding window approach for '{}'", closest_match_string);
+    
+    // Extract clean text from word boxes (like embedded text with markers)
+    let embedded_text = create_embedded_text_from_word_boxes(&word_boxes);
+    log!("WASM: Created embedded text: {}", &embedded_text.chars().take(200).collect::<String>());
+    
+    // Use the same algorithm as JS implementation
+    let Some(word_match) = find_js_style_match(&embedded_text, closest_match_string, &word_boxes) else {
+        log!("WASM: No matching word sequence found!");
+        return Err(MatchError::NoMatch);
+    };
+    
+    log!("WASM: Found {} matching boxes", word_match.word_boxes.len());
+    Ok(word_match)
+}
+
+/// Locate a search string in hOCR content using word-level coordinates
+/// Returns the union box of the matched words together with the window similarity
+pub fn find_hocr_match(hocr_content: &str, closest_match_string: &str, options: &ExtractOptions) -> Option<HocrMatchResult> {
+    let word_match = try_match_word_boxes_with_options(hocr_content, closest_match_string, options).ok()?;
+    
+    // Punctuation-only trailing words stay in the match but not in its geometry
+    let mut geometry_boxes: &[WordBox] = &word_match.word_boxes;
+    if options.trim_trailing_punct {
+        while geometry_boxes.len() > 1 && is_punctuation_only(&geometry_boxes[geometry_boxes.len() - 1].text) {
+            geometry_boxes = &geometry_boxes[..geometry_boxes.len() - 1];
+        }
+    }
+    
+    // Calculate bounding box from matching words
+    let mut bbox = calculate_bounding_box_from_words(geometry_boxes)?;
+    if options.max_span_width.is_some_and(|max_width| bbox.x2 - bbox.x1 > max_width) {
+        log!("WASM: Match spans {} pixels, wider than the cap", bbox.x2 - bbox.x1);
+        return None;
+    }
+    if options.clamp_to_page {
+        if let Some(page) = extract_page_dimensions(hocr_content) {
+            bbox = BoundingBox::new(
+                bbox.x1.clamp(0.0, page.width),
+                bbox.y1.clamp(0.0, page.height),
+                bbox.x2.clamp(0.0, page.width),
+                bbox.y2.clamp(0.0, page.height),
+            );
+        }
+    }
+    log!("WASM: Final bbox: [{}, {}, {}, {}]", bbox.x1, bbox.y1, bbox.x2, bbox.y2);
+    
+    Some(HocrMatchResult {
+        bbox,
+        similarity: word_match.similarity,
+        matched_text: word_match.word_boxes.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" "),
+        word_count: word_match.word_boxes.len(),
+    })
+}
+
+/// Extract bounding box coordinates using word-level coordinates (improved version)
+/// This version uses the original hOCR content to find word-level bounding boxes
+#[wasm_bindgen]
+pub fn extract_bounding_box_from_hocr(hocr_content: &str, closest_match_string: &str) -> Option<BoundingBox> {
+    find_hocr_match(hocr_content, closest_match_string, &ExtractOptions::default()).map(|m| m.bbox)
+}
+
+/// Locate the matched bounding box or explain why there is none
+/// `min_similarity` (optional) rejects weak matches with `BelowThreshold`
+pub fn locate_bounding_box(
+    hocr_content: &str,
+    search_string: &str,
+    min_similarity: Option<f64>,
+) -> Result<BoundingBox, MatchError> {
+    let word_match = try_match_word_boxes(hocr_content, search_string)?;
+    
+    if word_match.similarity < min_similarity.unwrap_or(0.0) {
+        return Err(MatchError::BelowThreshold);
+    }
+    
+    calculate_bounding_box_from_words(&word_match.word_boxes).ok_or(MatchError::NoMatch)
+}
+
+/// Extract the matched bounding box, throwing `{code, message}` on failure
+/// Codes: EmptyInput, BlankSearch, NoWordBoxes, NoMatch, BelowThreshold
+#[wasm_bindgen]
+pub fn try_extract_bounding_box(
+    hocr_content: &str,
+    search_string: &str,
+    min_similarity: Option<f64>,
+) -> Result<BoundingBox, JsValue> {
+    locate_bounding_box(hocr_content, search_string, min_similarity).map_err(JsValue::from)
+}
+
+/// Why a search string has no match in hOCR content, as `"Code: message"`
+/// None when the string does match
+#[wasm_bindgen]
+pub fn diagnose_match(hocr_content: &str, search_string: &str) -> Option<String> {
+    locate_bounding_box(hocr_content, search_string, None).err().map(|error| error.to_string())
+}
+
+/// Extract the matched bounding box with extraction options applied
+#[wasm_bindgen]
+pub fn extract_bounding_box_from_hocr_with_options(
+    hocr_content: &str,
+    closest_match_string: &str,
+    options: &ExtractOptions,
+) -> Option<BoundingBox> {
+    find_hocr_match(hocr_content, closest_match_string, options).map(|m| m.bbox)
+}
+
+/// Extract the matched box together with its similarity score in a single call
+/// Returns `{x1, y1, x2, y2, similarity, confidence, matchedText, wordCount}`
+#[wasm_bindgen]
+pub fn extract_match_result_from_hocr(hocr_content: &str, search_string: &str) -> Option<js_sys::Object> {
+    let result = find_hocr_match(hocr_content, search_string, &ExtractOptions::default())?;
+    Some(hocr_match_to_js(result))
+}
+
+/// `{x1, y1, x2, y2, similarity, confidence, matchedText, wordCount}` for a match
+fn hocr_match_to_js(result: HocrMatchResult) -> js_sys::Object {
+    let js_result = js_sys::Object::new();
+    js_sys::Reflect::set(&js_result, &"x1".into(), &result.bbox.x1.into()).unwrap();
+    js_sys::Reflect::set(&js_result, &"y1".into(), &result.bbox.y1.into()).unwrap();
+    js_sys::Reflect::set(&js_result, &"x2".into(), &result.bbox.x2.into()).unwrap();
+    js_sys::Reflect::set(&js_result, &"y2".into(), &result.bbox.y2.into()).unwrap();
+    js_sys::Reflect::set(&js_result, &"similarity".into(), &result.similarity.into()).unwrap();
+    js_sys::Reflect::set(&js_result, &"confidence".into(), &classify_match_confidence(result.similarity, None, None).into()).unwrap();
+    js_sys::Reflect::set(&js_result, &"matchedText".into(), &result.matched_text.into()).unwrap();
+    js_sys::Reflect::set(&js_result, &"wordCount".into(), &(result.word_count as u32).into()).unwrap();
+    
+    js_result
+}
+
+/// Best match whose words all sit on one visual line (grouped by vertical overlap)
+/// Each line is matched on its own, so a better match straddling a line break loses
+/// to the best single-line one; None when no line matches at all
+pub fn best_single_line_match(hocr_content: &str, search_string: &str) -> Option<HocrMatchResult> {
+    let word_boxes = extract_word_bounding_boxes(hocr_content);
+    
+    let mut best: Option<WordBoxMatch> = None;
+    for line in split_visual_lines(word_boxes) {
+        let embedded_text = create_embedded_text_from_word_boxes(&line);
+        let Some(word_match) = find_js_style_match(&embedded_text, search_string, &line) else {
+            continue;
+        };
+        if best.as_ref().is_none_or(|b| word_match.similarity > b.similarity) {
+            best = Some(word_match);
+        }
+    }
+    
+    let word_match = best?;
+    Some(HocrMatchResult {
+        bbox: calculate_bounding_box_from_words(&word_match.word_boxes)?,
+        similarity: word_match.similarity,
+        matched_text: word_match.word_boxes.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" "),
+        word_count: word_match.word_boxes.len(),
+    })
+}
+
+/// Best match confined to a single visual line, for one-line form fields
+/// Returns the same shape as `extract_match_result_from_hocr`
+#[wasm_bindgen]
+pub fn find_single_line_match(hocr_content: &str, search_string: &str) -> Option<js_sys::Object> {
+    best_single_line_match(hocr_content, search_string).map(hocr_match_to_js)
+}
+
+/// Matched box as fractions (0–1) of the first page's width and height
+/// None when there is no match or the page declares no usable dimensions
+pub fn normalized_match_box(hocr_content: &str, search_string: &str) -> Option<BoundingBox> {
+    let page = extract_page_dimensions(hocr_content)?;
+    if page.width <= 0.0 || page.height <= 0.0 {
+        return None;
+    }
+    
+    let bbox = extract_bounding_box_from_hocr(hocr_content, search_string)?;
+    Some(BoundingBox::new(
+        bbox.x1 / page.width,
+        bbox.y1 / page.height,
+        bbox.x2 / page.width,
+        bbox.y2 / page.height,
+    ))
+}
+
+/// Extract the matched box in resolution-independent page coordinates
+/// Returns `{x1, y1, x2, y2}`, each divided by the page width or height
+#[wasm_bindgen]
+pub fn extract_normalized_box_from_hocr(hocr_content: &str, search_string: &str) -> Option<js_sys::Object> {
+    let bbox = normalized_match_box(hocr_content, search_string)?;
+    
+    let js_box = js_sys::Object::new();
+    js_sys::Reflect::set(&js_box, &"x1".into(), &bbox.x1.into()).unwrap();
+    js_sys::Reflect::set(&js_box, &"y1".into(), &bbox.y1.into()).unwrap();
+    js_sys::Reflect::set(&js_box, &"x2".into(), &bbox.x2.into()).unwrap();
+    js_sys::Reflect::set(&js_box, &"y2".into(), &bbox.y2.into()).unwrap();
+    
+    Some(js_box)
+}
+
+/// Best match on every page (zero-based index) whose similarity reaches `min_similarity`
+pub fn match_each_page(hocr_content: &str, search_string: &str, min_similarity: f64) -> Vec<(usize, HocrMatchResult)> {
+    split_pages(hocr_content)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(page, page_content)| {
+            find_hocr_match(page_content, search_string, &ExtractOptions::default())
+                .filter(|m| m.similarity >= min_similarity)
+                .map(|m| (page, m))
+        })
+        .collect()
+}
+
+/// Match a search string separately on each `ocr_page` of concatenated hOCR
+/// Returns `{page, similarity, x1, y1, x2, y2}` for every page meeting the threshold
+/// (`page` is zero-based)
+#[wasm_bindgen]
+pub fn find_match_per_page(hocr_content: &str, search_string: &str, min_similarity: f64) -> js_sys::Array {
+    let results = js_sys::Array::new();
+    
+    for (page, result) in match_each_page(hocr_content, search_string, min_similarity) {
+        let js_result = js_sys::Object::new();
+        js_sys::Reflect::set(&js_result, &"page".into(), &(page as u32).into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"similarity".into(), &result.similarity.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"x1".into(), &result.bbox.x1.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"y1".into(), &result.bbox.y1.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"x2".into(), &result.bbox.x2.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"y2".into(), &result.bbox.y2.into()).unwrap();
+        results.push(&js_result);
+    }
+    
+    results
+}
+
+/// Best match of one search term on one page of a multi-page document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageTermMatch {
+    /// Zero-based page index
+    pub page: usize,
+    pub term: String,
+    pub similarity: f64,
+    #[serde(flatten)]
+    pub bbox: BoundingBox,
+    /// Index of the first matched word box within the page
+    pub start_index: usize,
+}
+
+/// Every search term matched separately on each page, flattened into one list sorted
+/// by page then reading order (query order on ties); matches below `min_similarity`
+/// are dropped
+pub fn match_terms_across_pages(hocr_content: &str, search_strings: &[String], min_similarity: f64) -> Vec<PageTermMatch> {
+    let mut matches: Vec<PageTermMatch> = Vec::new();
+    for (page, page_content) in split_pages(hocr_content).into_iter().enumerate() {
+        for term in search_strings {
+            let Some(word_match) = match_word_boxes(page_content, term) else {
+                continue;
+            };
+            if word_match.similarity < min_similarity {
+                continue;
             }
+            let Some(bbox) = calculate_bounding_box_from_words(&word_match.word_boxes) else {
+                continue;
+            };
+            matches.push(PageTermMatch {
+                page,
+                term: term.clone(),
+                similarity: word_match.similarity,
+                bbox,
+                start_index: word_match.first_box,
+            });
         }
     }
     
-    web_sys::console::log_1(&format!("WASM: Best match: cleaned_start={}, similarity={:.3}", best_cleaned_start_index, best_similarity).into());
+    // Stable sort, so terms starting at the same word stay in query order
+    matches.sort_by_key(|m| (m.page, m.start_index));
+    matches
+}
+
+/// Search several terms across a multi-page document in one call
+/// Returns `{page, term, similarity, x1, y1, x2, y2, startIndex}` sorted by page, then
+/// by where the match starts on the page (`page` is zero-based)
+#[wasm_bindgen]
+pub fn find_all_across_pages(hocr_content: &str, search_strings: &js_sys::Array, min_similarity: f64) -> js_sys::Array {
+    let search_strings: Vec<String> = search_strings.iter().filter_map(|v| v.as_string()).collect();
+    let matches = match_terms_across_pages(hocr_content, &search_strings, min_similarity);
     
-    // Only proceed if we have a reasonable similarity (copying JS threshold logic)
-    if best_similarity <= 0.0 {
-        web_sys::console::log_1(&"WASM: No match found - similarity is 0".into());
+    // The flattened bbox serializes through a map, which must become a plain object
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+    matches.serialize(&serializer).unwrap().unchecked_into()
+}
+
+/// Every non-overlapping window scoring at least `min_similarity`, in reading order
+/// Higher-scoring windows win when candidates overlap; `max_results` keeps only that
+/// many of the highest-scoring ones
+fn match_all_occurrences(
+    hocr_content: &str,
+    search_string: &str,
+    min_similarity: f64,
+    max_results: Option<usize>,
+) -> Vec<WordBoxMatch> {
+    let word_boxes = extract_word_bounding_boxes(hocr_content);
+    let cleaned_words: Vec<&str> = word_boxes.iter().flat_map(|w| w.clean_text.split_whitespace()).collect();
+    let cleaned_search = clean_word(search_string);
+    let search_words: Vec<&str> = cleaned_search.split_whitespace().collect();
+    
+    let window_size = search_words.len();
+    if window_size == 0 || window_size > cleaned_words.len() {
         return Vec::new();
     }
     
-    // Map cleaned text indices back to word boxes using the exact JS algorithm
-    let best_cleaned_end_index = best_cleaned_start_index + window_size;
+    let candidates: Vec<(usize, f64)> = (0..=cleaned_words.len() - window_size)
+        .map(|i| (i, js_sequence_similarity(&cleaned_words[i..i + window_size], &search_words)))
+        .filter(|&(_, similarity)| similarity > 0.0 && similarity >= min_similarity)
+        .collect();
     
-    // The key insight: we need to find which word boxes correspond to the cleaned word indices
-    // Since the word boxes are extracted in order, we need to map the cleaned word positions
-    // back to the original word box positions
+    select_non_overlapping_windows(candidates, window_size, max_results)
+        .into_iter()
+        .filter_map(|(start, similarity)| {
+            let indices = box_indices_for_cleaned_range(&cleaned_words, &word_boxes, start, start + window_size);
+            WordBoxMatch::from_indices(&word_boxes, &indices, similarity)
+        })
+        .collect()
+}
+
+/// Best window when each word counts by its inverse document frequency on the page,
+/// so a rare distinctive word outweighs several common ones
+pub fn idf_weighted_match(hocr_content: &str, search_string: &str) -> Option<HocrMatchResult> {
+    let word_boxes = extract_word_bounding_boxes(hocr_content);
+    let cleaned_words: Vec<&str> = word_boxes.iter().flat_map(|w| w.clean_text.split_whitespace()).collect();
+    let cleaned_search = clean_word(search_string);
+    let search_words: Vec<&str> = cleaned_search.split_whitespace().collect();
     
-    web_sys::console::log_1(&format!("WASM: Mapping cleaned indices [{}, {}) back to word boxes", 
-        best_cleaned_start_index, best_cleaned_end_index).into());
+    let window_size = search_words.len();
+    if window_size == 0 || window_size > cleaned_words.len() {
+        return None;
+    }
     
-    // Create a mapping from word box text to cleaned word positions
-    let mut word_box_to_cleaned_index = Vec::new();
-    let mut cleaned_word_index = 0;
+    let mut frequencies: HashMap<&str, usize> = HashMap::new();
+    for word in &cleaned_words {
+        *frequencies.entry(word).or_default() += 1;
+    }
     
-    // Debug: show first few word boxes and cleaned words
-    web_sys::console::log_1(&format!("WASM: First 10 word boxes: {:?}", 
-        word_boxes.iter().take(10).map(|wb| &wb.text).collect::<Vec<_>>()).into());
-    web_sys::console::log_1(&format!("WASM: First 10 cleaned words: {:?}", 
-        cleaned_words.iter().take(10).collect::<Vec<_>>()).into());
+    let mut best: Option<(usize, f64)> = None;
+    for i in 0..=cleaned_words.len() - window_size {
+        let similarity = idf_sequence_similarity(&cleaned_words[i..i + window_size], &search_words, &frequencies);
+        if similarity > best.map_or(0.0, |(_, s)| s) {
+            best = Some((i, similarity));
+        }
+    }
     
-    for (box_index, word_box) in word_boxes.iter().enumerate() {
-        // Check if this word box text matches the cleaned word at this position
-        if cleaned_word_index < cleaned_words.len() {
-            let cleaned_word = cleaned_words[cleaned_word_index];
-            let box_original_text = word_box.text.trim();
-            let box_clean_text = word_box.clean_text.trim();
-            
-            // Try multiple matching strategies
-            let matches = box_original_text.to_lowercase() == cleaned_word.to_lowercase() ||
-                         box_clean_text.to_lowercase() == cleaned_word.to_lowercase() ||
-                         box_original_text == cleaned_word ||
-                         box_clean_text == cleaned_word;
-            
-            if matches {
-                word_box_to_cleaned_index.push((box_index, cleaned_word_index));
-                cleaned_word_index += 1;
-                
-                if box_index < 20 {  // Debug first few matches
-                    web_sys::console::log_1(&format!("WASM: Match {}: box[{}]='{}' -> cleaned[{}]='{}'", 
-                        word_box_to_cleaned_index.len() - 1, box_index, box_original_text, cleaned_word_index - 1, cleaned_word).into());
-                }
-            } else if box_index < 20 {  // Debug first few non-matches
-                web_sys::console::log_1(&format!("WASM: NO match: box[{}]='{}' (clean='{}') vs cleaned[{}]='{}'", 
-                    box_index, box_original_text, box_clean_text, cleaned_word_index, cleaned_word).into());
-            }
+    let (start, similarity) = best?;
+    let indices = box_indices_for_cleaned_range(&cleaned_words, &word_boxes, start, start + window_size);
+    let word_match = WordBoxMatch::from_indices(&word_boxes, &indices, similarity)?;
+    Some(HocrMatchResult {
+        bbox: calculate_bounding_box_from_words(&word_match.word_boxes)?,
+        similarity,
+        matched_text: word_match.word_boxes.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" "),
+        word_count: word_match.word_boxes.len(),
+    })
+}
+
+/// Closest match with words weighted by inverse document frequency over the page
+/// Returns the same shape as `extract_match_result_from_hocr`
+#[wasm_bindgen]
+pub fn find_closest_match_idf(hocr_content: &str, search_string: &str) -> Option<js_sys::Object> {
+    idf_weighted_match(hocr_content, search_string).map(hocr_match_to_js)
+}
+
+/// Byte range `[start, end)` of the raw hOCR covering the matched `ocrx_word` elements,
+/// from the first word's opening tag to the last word's `</span>`
+pub fn match_hocr_byte_span(hocr_content: &str, search_string: &str) -> Option<(usize, usize)> {
+    let word_match = match_word_boxes(hocr_content, search_string)?;
+    let start = word_match.word_boxes.iter().map(|w| w.source_span.0).min()?;
+    let end = word_match.word_boxes.iter().map(|w| w.source_span.1).max()?;
+    
+    Some((start, end))
+}
+
+/// Where a match sits in the original hOCR string, for tools that edit the source
+/// Returns `{startByte, endByte}` (UTF-8 byte offsets, end exclusive)
+#[wasm_bindgen]
+pub fn extract_match_hocr_span(hocr_content: &str, search_string: &str) -> Option<js_sys::Object> {
+    let (start, end) = match_hocr_byte_span(hocr_content, search_string)?;
+    
+    let js_span = js_sys::Object::new();
+    js_sys::Reflect::set(&js_span, &"startByte".into(), &(start as u32).into()).unwrap();
+    js_sys::Reflect::set(&js_span, &"endByte".into(), &(end as u32).into()).unwrap();
+    
+    Some(js_span)
+}
+
+/// One occurrence of a wildcard pattern: the union box, the matched text and the words
+/// that filled each `*`, in order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WildcardMatch {
+    pub bbox: BoundingBox,
+    pub text: String,
+    pub captures: Vec<String>,
+}
+
+/// Every non-overlapping run of words matching `pattern` exactly, in reading order
+/// Pattern tokens are cleaned like search words; a `*` token matches any single word
+pub fn wildcard_matches(hocr_content: &str, pattern: &str) -> Vec<WildcardMatch> {
+    let tokens: Vec<String> = pattern.split_whitespace()
+        .map(|token| if token == "*" { token.to_string() } else { clean_word(token) })
+        .collect();
+    let word_boxes = extract_word_bounding_boxes(hocr_content);
+    if tokens.is_empty() || tokens.len() > word_boxes.len() {
+        return Vec::new();
+    }
+    
+    let mut matches = Vec::new();
+    let mut start = 0;
+    while start + tokens.len() <= word_boxes.len() {
+        let window = &word_boxes[start..start + tokens.len()];
+        let fits = window.iter().zip(&tokens).all(|(word, token)| token == "*" || word.clean_text == *token);
+        if !fits {
+            start += 1;
+            continue;
+        }
+        
+        if let Some(bbox) = calculate_bounding_box_from_words(window) {
+            matches.push(WildcardMatch {
+                bbox,
+                text: window.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" "),
+                captures: window.iter().zip(&tokens).filter(|(_, token)| *token == "*").map(|(w, _)| w.text.clone()).collect(),
+            });
         }
+        start += tokens.len();
     }
     
-    web_sys::console::log_1(&format!("WASM: Mapped {} word boxes to cleaned positions", word_box_to_cleaned_index.len()).into());
+    matches
+}
+
+/// Locate fields such as "No. *" or "Date: *", where `*` stands for any one word
+/// Returns `{x1, y1, x2, y2, text, captures}` per occurrence; `captures` holds the words
+/// matched by each `*`
+#[wasm_bindgen]
+pub fn find_wildcard_matches(hocr_content: &str, pattern: &str) -> js_sys::Array {
+    let results = js_sys::Array::new();
     
-    // Find the word boxes that correspond to our match
-    let mut result_boxes = Vec::new();
-    for (box_index, cleaned_index) in word_box_to_cleaned_index {
-        if cleaned_index >= best_cleaned_start_index && cleaned_index < best_cleaned_end_index {
-            result_boxes.push(word_boxes[box_index].clone());
+    for found in wildcard_matches(hocr_content, pattern) {
+        let captures = js_sys::Array::new();
+        for capture in found.captures {
+            captures.push(&capture.into());
         }
+        
+        let js_result = js_sys::Object::new();
+        js_sys::Reflect::set(&js_result, &"x1".into(), &found.bbox.x1.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"y1".into(), &found.bbox.y1.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"x2".into(), &found.bbox.x2.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"y2".into(), &found.bbox.y2.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"text".into(), &found.text.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"captures".into(), &captures).unwrap();
+        results.push(&js_result);
     }
     
-    web_sys::console::log_1(&format!("WASM: Found {} word boxes for match", result_boxes.len()).into());
-    result_boxes
+    results
 }
 
-/// Exact copy of JS sequenceSimilarity algorithm
-fn js_sequence_similarity(seq1: &[&str], seq2: &[&str]) -> f64 {
-    let max_length = std::cmp::max(seq1.len(), seq2.len());
-    if max_length == 0 {
-        return 1.0;
+/// Union box of every non-overlapping occurrence of a search string, parsed once
+/// Returns `{x1, y1, x2, y2, similarity}` per occurrence in reading order; `max_results`
+/// (optional) keeps only that many of the highest-scoring occurrences, so a common
+/// single word cannot return thousands of boxes
+#[wasm_bindgen]
+pub fn extract_all_boxes_for_match(
+    hocr_content: &str,
+    search_string: &str,
+    min_similarity: f64,
+    max_results: Option<usize>,
+) -> js_sys::Array {
+    let results = js_sys::Array::new();
+    
+    for occurrence in match_all_occurrences(hocr_content, search_string, min_similarity, max_results) {
+        let Some(bbox) = calculate_bounding_box_from_words(&occurrence.word_boxes) else {
+            continue;
+        };
+        let js_result = js_sys::Object::new();
+        js_sys::Reflect::set(&js_result, &"x1".into(), &bbox.x1.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"y1".into(), &bbox.y1.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"x2".into(), &bbox.x2.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"y2".into(), &bbox.y2.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"similarity".into(), &occurrence.similarity.into()).unwrap();
+        results.push(&js_result);
     }
     
-    let matches = seq1.iter()
-        .zip(seq2.iter())
-        .filter(|(a, b)| a == b)
-        .count();
+    results
+}
+
+/// Box of the `n`th (0-based) non-overlapping occurrence in reading order, e.g. the
+/// second of several repeated headers; None when there are fewer than `n + 1`
+#[wasm_bindgen]
+pub fn extract_nth_match_box(hocr_content: &str, search_string: &str, n: usize, min_similarity: f64) -> Option<BoundingBox> {
+    let occurrence = match_all_occurrences(hocr_content, search_string, min_similarity, None).into_iter().nth(n)?;
+    calculate_bounding_box_from_words(&occurrence.word_boxes)
+}
+
+/// The `k` best non-overlapping windows for a query, highest similarity first
+/// (reading order on ties)
+pub fn candidate_matches(hocr_content: &str, search_string: &str, k: usize) -> Vec<HocrMatchResult> {
+    let mut candidates: Vec<HocrMatchResult> = match_all_occurrences(hocr_content, search_string, 0.0, Some(k))
+        .into_iter()
+        .filter_map(|occurrence| {
+            Some(HocrMatchResult {
+                bbox: calculate_bounding_box_from_words(&occurrence.word_boxes)?,
+                similarity: occurrence.similarity,
+                matched_text: occurrence.word_boxes.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" "),
+                word_count: occurrence.word_boxes.len(),
+            })
+        })
+        .collect();
     
-    matches as f64 / max_length as f64
+    candidates.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    candidates
 }
 
-/// Simple string similarity for legacy function
-fn calculate_string_similarity(s1: &str, s2: &str) -> f64 {
-    let chars1: Vec<char> = s1.chars().collect();
-    let chars2: Vec<char> = s2.chars().collect();
+/// Top-`k` candidate regions for a "did you mean this one?" picker
+/// Returns `{x1, y1, x2, y2, similarity, confidence, matchedText, wordCount}` per
+/// candidate, best first
+#[wasm_bindgen]
+pub fn candidate_boxes_for_match(hocr_content: &str, search_string: &str, k: usize) -> js_sys::Array {
+    candidate_matches(hocr_content, search_string, k)
+        .into_iter()
+        .map(hocr_match_to_js)
+        .collect()
+}
+
+/// Words searched on either side of the previous match by `find_box_incremental`
+const INCREMENTAL_SLACK_WORDS: usize = 2;
+
+/// hOCR content parsed once and reused across many queries
+/// Word boxes and the embedded text are computed at construction time
+#[wasm_bindgen]
+pub struct HocrDocument {
+    word_boxes: Vec<WordBox>,
+    embedded_text: String,
+    /// Last query answered by `find_box_incremental` and the index of its first matched box
+    last_match: Option<(String, usize)>,
+}
+
+#[wasm_bindgen]
+impl HocrDocument {
+    #[wasm_bindgen(constructor)]
+    pub fn new(hocr_content: &str) -> HocrDocument {
+        let word_boxes = extract_word_bounding_boxes(hocr_content);
+        let embedded_text = create_embedded_text_from_word_boxes(&word_boxes);
+        
+        HocrDocument { word_boxes, embedded_text, last_match: None }
+    }
     
-    let matching = chars1.iter()
-        .zip(chars2.iter())
-        .filter(|(a, b)| a == b)
-        .count();
+    /// Number of word boxes parsed from the document
+    pub fn word_count(&self) -> usize {
+        self.word_boxes.len()
+    }
     
-    let max_len = std::cmp::max(chars1.len(), chars2.len());
-    if max_len == 0 {
-        1.0
-    } else {
-        matching as f64 / max_len as f64
+    /// Bounding box of the best match for `search_string`
+    pub fn find_box(&self, search_string: &str) -> Option<BoundingBox> {
+        let word_match = find_js_style_match(&self.embedded_text, search_string, &self.word_boxes)?;
+        calculate_bounding_box_from_words(&word_match.word_boxes)
+    }
+    
+    /// `find_box` for search-as-you-type: when `new_query` extends `prev_query` (the last
+    /// query answered here), only the words around the previous match are searched first;
+    /// the whole document is searched unless that region holds an exact match that a fresh
+    /// `find_box` would also pick
+    pub fn find_box_incremental(&mut self, prev_query: &str, new_query: &str) -> Option<BoundingBox> {
+        let hint = self.last_match.take()
+            .filter(|(query, _)| query == prev_query && new_query.starts_with(prev_query))
+            .map(|(_, first_box)| first_box);
+        
+        let narrowed = hint.and_then(|first_box| {
+            let new_word_count = clean_word(new_query).split_whitespace().count();
+            let start = first_box.saturating_sub(INCREMENTAL_SLACK_WORDS);
+            let end = (first_box + new_word_count + INCREMENTAL_SLACK_WORDS).min(self.word_boxes.len());
+            let region = &self.word_boxes[start..end];
+            
+            find_js_style_match(&create_embedded_text_from_word_boxes(region), new_query, region)
+                .filter(|m| m.similarity >= 1.0 && self.is_preferred_exact_match(new_query, start + m.first_box))
+                .map(|m| (m, start))
+        });
+        
+        let (word_match, offset) = match narrowed {
+            Some(found) => found,
+            None => (find_js_style_match(&self.embedded_text, new_query, &self.word_boxes)?, 0),
+        };
+        self.last_match = Some((new_query.to_string(), offset + word_match.first_box));
+        
+        calculate_bounding_box_from_words(&word_match.word_boxes)
+    }
+    
+    /// Whether an exact match starting at box `first_box` is the one a full search returns:
+    /// no earlier window reaches the 0.95 early exit and no later window ties it exactly
+    fn is_preferred_exact_match(&self, search_string: &str, first_box: usize) -> bool {
+        let cleaned_words: Vec<&str> = self.word_boxes.iter().flat_map(|w| w.clean_text.split_whitespace()).collect();
+        let cleaned_search = clean_word(search_string);
+        let search_words: Vec<&str> = cleaned_search.split_whitespace().collect();
+        let match_start: usize = self.word_boxes[..first_box].iter().map(|w| w.clean_text.split_whitespace().count()).sum();
+        
+        let window_size = search_words.len();
+        (0..=cleaned_words.len().saturating_sub(window_size))
+            .filter(|&i| i != match_start && i + window_size <= cleaned_words.len())
+            .all(|i| {
+                let similarity = js_sequence_similarity(&cleaned_words[i..i + window_size], &search_words);
+                if i < match_start { similarity < 0.95 } else { similarity < 1.0 }
+            })
+    }
+    
+    /// Bounding boxes for several search strings, one entry per query
+    /// Each entry is `{searchQuery, x1, y1, x2, y2}`, or `{searchQuery}` alone when unmatched
+    pub fn find_boxes(&self, search_strings: &js_sys::Array) -> js_sys::Array {
+        let results = js_sys::Array::new();
+        
+        for search_str in search_strings.iter().filter_map(|v| v.as_string()) {
+            let js_result = js_sys::Object::new();
+            if let Some(bbox) = self.find_box(&search_str) {
+                js_sys::Reflect::set(&js_result, &"x1".into(), &bbox.x1.into()).unwrap();
+                js_sys::Reflect::set(&js_result, &"y1".into(), &bbox.y1.into()).unwrap();
+                js_sys::Reflect::set(&js_result, &"x2".into(), &bbox.x2.into()).unwrap();
+                js_sys::Reflect::set(&js_result, &"y2".into(), &bbox.y2.into()).unwrap();
+            }
+            js_sys::Reflect::set(&js_result, &"searchQuery".into(), &search_str.into()).unwrap();
+            
+            results.push(&js_result);
+        }
+        
+        results
     }
 }
 
-/// Calculate bounding box from a list of word boxes
-fn calculate_bounding_box_from_words(word_boxes: &[WordBox]) -> Option<BoundingBox> {
-    if word_boxes.is_empty() {
+/// Union box of the words at cleaned-word indices `[start_index, end_index)`, in the index
+/// space of `find_closest_match` run on `extract_embedded_text_from_hocr(hocr_content)`,
+/// so a `MatchResult` can be turned straight into geometry
+#[wasm_bindgen]
+pub fn word_box_for_index_range(hocr_content: &str, start_index: usize, end_index: usize) -> Option<BoundingBox> {
+    if start_index >= end_index {
         return None;
     }
     
-    // Find the minimum and maximum coordinates
-    let min_x = word_boxes.iter().map(|w| w.x1).fold(f64::INFINITY, f64::min);
-    let min_y = word_boxes.iter().map(|w| w.y1).fold(f64::INFINITY, f64::min);
-    let max_x = word_boxes.iter().map(|w| w.x2).fold(f64::NEG_INFINITY, f64::max);
-    let max_y = word_boxes.iter().map(|w| w.y2).fold(f64::NEG_INFINITY, f64::max);
+    // Walk the same words the embedded text is built from; a span may hold several
+    // cleaned words, and one without a bbox still takes up its indices
+    let mut word_index = 0;
+    let mut matched_boxes: Vec<BoundingBox> = Vec::new();
+    for (word, bbox) in embedded_words_with_boxes(hocr_content) {
+        let word_end = word_index + word.split_whitespace().count();
+        if word_index < end_index && word_end > start_index {
+            matched_boxes.extend(bbox);
+        }
+        word_index = word_end;
+    }
+    if end_index > word_index {
+        return None;
+    }
     
-    Some(BoundingBox::new(min_x, min_y, max_x, max_y))
+    matched_boxes.into_iter().reduce(|union, bbox| {
+        BoundingBox::new(union.x1.min(bbox.x1), union.y1.min(bbox.y1), union.x2.max(bbox.x2), union.y2.max(bbox.y2))
+    })
 }
 
+/// Return the index of the `ocr_line` on which the best match for `search_string` begins
+#[wasm_bindgen]
+pub fn extract_match_line_index(hocr_content: &str, search_string: &str) -> Option<u32> {
+    let word_match = match_word_boxes(hocr_content, search_string)?;
+    word_match.word_boxes.first().map(|w| w.line_index as u32)
+}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Closest match for each search string, with the union box of its words, from a
+/// single parse of the hOCR; queries below `min_similarity` are left out
+pub fn collect_matches_with_boxes(
+    hocr_content: &str,
+    search_strings: &[String],
+    min_similarity: f64,
+) -> Vec<(String, MatchResult, BoundingBox)> {
+    let word_boxes = extract_word_bounding_boxes(hocr_content);
+    let embedded_text = create_embedded_text_from_word_boxes(&word_boxes);
+    let cleaned_words: Vec<&str> = word_boxes.iter().flat_map(|w| w.clean_text.split_whitespace()).collect();
+    
+    search_strings.iter()
+        .filter_map(|search_string| {
+            let match_result = find_closest_match(&embedded_text, &clean_word(search_string))?;
+            if match_result.similarity < min_similarity {
+                return None;
+            }
+            let indices = box_indices_for_cleaned_range(&cleaned_words, &word_boxes, match_result.start_index, match_result.end_index);
+            let matched_boxes: Vec<WordBox> = indices.iter().map(|&i| word_boxes[i].clone()).collect();
+            let bbox = calculate_bounding_box_from_words(&matched_boxes)?;
+            
+            Some((search_string.clone(), match_result, bbox))
+        })
+        .collect()
+}
 
-    #[test]
-    fn test_extract_embedded_text() {
-        let hocr_sample = r#"
-        <p class='ocr_par'>
-            <span class='ocr_line' title='bbox 100 200 300 400'>
-                <span class='ocrx_word'>Hello</span>
-                <span class='ocrx_word'>World</span>
-            </span>
-        </p>"#;
-        
-        let result = extract_embedded_text_from_hocr(hocr_sample);
-        assert!(result.contains("[[PARAGRAPH]]"));
-        assert!(result.contains("[[LINE 100 200 300 400]]"));
-        assert!(result.contains("Hello"));
-        assert!(result.contains("World"));
+/// `find_multiple_matches` over hOCR, with each match's box attached
+/// Returns `{text, similarity, startIndex, endIndex, searchQuery, x1, y1, x2, y2}` per matched query
+#[wasm_bindgen]
+pub fn find_multiple_matches_with_boxes(
+    hocr_content: &str,
+    search_strings: &js_sys::Array,
+    min_similarity: f64,
+) -> js_sys::Array {
+    let queries: Vec<String> = search_strings.iter().filter_map(|v| v.as_string()).collect();
+    let results = js_sys::Array::new();
+    
+    for (search_str, match_result, bbox) in collect_matches_with_boxes(hocr_content, &queries, min_similarity) {
+        let js_result = js_sys::Object::new();
+        js_sys::Reflect::set(&js_result, &"text".into(), &match_result.text().into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"similarity".into(), &match_result.similarity.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"startIndex".into(), &(match_result.start_index as u32).into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"endIndex".into(), &(match_result.end_index as u32).into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"searchQuery".into(), &search_str.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"x1".into(), &bbox.x1.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"y1".into(), &bbox.y1.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"x2".into(), &bbox.x2.into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"y2".into(), &bbox.y2.into()).unwrap();
+        results.push(&js_result);
+    }
+    
+    results
+}
+
+/// Number of search strings whose match begins on each line, as `(line_index, count)`
+/// in line order; matches below `min_similarity` are ignored
+pub fn count_matches_by_line(hocr_content: &str, search_strings: &[String], min_similarity: f64) -> Vec<(usize, usize)> {
+    let word_boxes = extract_word_bounding_boxes(hocr_content);
+    let embedded_text = create_embedded_text_from_word_boxes(&word_boxes);
+    
+    let mut counts: Vec<(usize, usize)> = Vec::new();
+    for search_string in search_strings {
+        let Some(word_match) = find_js_style_match(&embedded_text, search_string, &word_boxes) else {
+            continue;
+        };
+        if word_match.similarity < min_similarity {
+            continue;
+        }
+        let line_index = word_match.word_boxes[0].line_index;
+        match counts.iter_mut().find(|(line, _)| *line == line_index) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((line_index, 1)),
+        }
+    }
+    
+    counts.sort_by_key(|&(line, _)| line);
+    counts
+}
+
+/// How many of the search strings matched on each line, for layout analysis
+/// Returns `{lineIndex, matchCount}` for every line where at least one match begins
+#[wasm_bindgen]
+pub fn match_density_by_line(hocr_content: &str, search_strings: &js_sys::Array, min_similarity: f64) -> js_sys::Array {
+    let search_strings: Vec<String> = search_strings.iter().filter_map(|v| v.as_string()).collect();
+    let results = js_sys::Array::new();
+    
+    for (line_index, match_count) in count_matches_by_line(hocr_content, &search_strings, min_similarity) {
+        let js_result = js_sys::Object::new();
+        js_sys::Reflect::set(&js_result, &"lineIndex".into(), &(line_index as u32).into()).unwrap();
+        js_sys::Reflect::set(&js_result, &"matchCount".into(), &(match_count as u32).into()).unwrap();
+        results.push(&js_result);
+    }
+    
+    results
+}
+
+/// Spacing between consecutive matched words, for spotting matches that jumped regions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchGaps {
+    /// `(horizontal, vertical)` gap per consecutive pair; the horizontal gap is 0
+    /// when the pair wraps onto another line
+    pub gaps: Vec<(f64, f64)>,
+    /// Some gap exceeds `SUSPICIOUS_GAP_FACTOR` average word widths
+    pub suspicious: bool,
+}
+
+/// Gaps between consecutive matched word boxes, flagged when any is implausibly wide
+pub fn match_gaps(hocr_content: &str, search_string: &str) -> Option<MatchGaps> {
+    let word_match = match_word_boxes(hocr_content, search_string)?;
+    let words = &word_match.word_boxes;
+    
+    let gaps: Vec<(f64, f64)> = words.windows(2)
+        .map(|pair| {
+            let (prev, next) = (&pair[0], &pair[1]);
+            let horizontal = if prev.line_index == next.line_index { (next.x1 - prev.x2).max(0.0) } else { 0.0 };
+            let vertical = (next.y1.max(prev.y1) - next.y2.min(prev.y2)).max(0.0);
+            (horizontal, vertical)
+        })
+        .collect();
+    
+    let average_width = words.iter().map(|w| w.x2 - w.x1).sum::<f64>() / words.len() as f64;
+    let limit = average_width * SUSPICIOUS_GAP_FACTOR;
+    let suspicious = gaps.iter().any(|&(horizontal, vertical)| horizontal > limit || vertical > limit);
+    
+    Some(MatchGaps { gaps, suspicious })
+}
+
+/// Average word widths a gap may span before `match_gaps` calls the match suspicious
+const SUSPICIOUS_GAP_FACTOR: f64 = 3.0;
+
+/// Debug view of the spacing inside a match
+/// Returns `{gaps: [{horizontal, vertical}], suspicious}` in hOCR pixels
+#[wasm_bindgen]
+pub fn debug_match_gaps(hocr_content: &str, search_string: &str) -> Option<js_sys::Object> {
+    let report = match_gaps(hocr_content, search_string)?;
+    
+    let gaps = js_sys::Array::new();
+    for (horizontal, vertical) in report.gaps {
+        let js_gap = js_sys::Object::new();
+        js_sys::Reflect::set(&js_gap, &"horizontal".into(), &horizontal.into()).unwrap();
+        js_sys::Reflect::set(&js_gap, &"vertical".into(), &vertical.into()).unwrap();
+        gaps.push(&js_gap);
+    }
+    
+    let js_result = js_sys::Object::new();
+    js_sys::Reflect::set(&js_result, &"gaps".into(), &gaps).unwrap();
+    js_sys::Reflect::set(&js_result, &"suspicious".into(), &report.suspicious.into()).unwrap();
+    
+    Some(js_result)
+}
+
+/// Area-weighted center of the matched word boxes, as `(x, y)` in hOCR pixels
+pub fn match_centroid_point(hocr_content: &str, search_string: &str) -> Option<(f64, f64)> {
+    let word_match = match_word_boxes(hocr_content, search_string)?;
+    
+    let (mut area_sum, mut x_sum, mut y_sum) = (0.0, 0.0, 0.0);
+    for word in &word_match.word_boxes {
+        let area = (word.x2 - word.x1) * (word.y2 - word.y1);
+        area_sum += area;
+        x_sum += area * (word.x1 + word.x2) / 2.0;
+        y_sum += area * (word.y1 + word.y2) / 2.0;
+    }
+    if area_sum <= 0.0 {
+        return None;
+    }
+    
+    Some((x_sum / area_sum, y_sum / area_sum))
+}
+
+/// Center of a match for placing a marker, weighted by word area
+/// Returns `{x, y}` in hOCR pixels
+#[wasm_bindgen]
+pub fn match_centroid(hocr_content: &str, search_string: &str) -> Option<js_sys::Object> {
+    let (x, y) = match_centroid_point(hocr_content, search_string)?;
+    
+    let js_point = js_sys::Object::new();
+    js_sys::Reflect::set(&js_point, &"x".into(), &x.into()).unwrap();
+    js_sys::Reflect::set(&js_point, &"y".into(), &y.into()).unwrap();
+    
+    Some(js_point)
+}
+
+/// Mean height (`y2 - y1`) of the matched word boxes, in hOCR pixels
+/// Multiply by the transform's `scale_y` to get a PDF font size hint
+#[wasm_bindgen]
+pub fn average_matched_word_height(hocr_content: &str, search_string: &str) -> Option<f64> {
+    let word_match = match_word_boxes(hocr_content, search_string)?;
+    let total_height: f64 = word_match.word_boxes.iter().map(|w| w.y2 - w.y1).sum();
+    
+    Some(total_height / word_match.word_boxes.len() as f64)
+}
+
+/// Box of the largest (by area) word equal to `word`, ignoring case and surrounding punctuation
+/// Useful for picking a heading over body-text occurrences of the same word
+#[wasm_bindgen]
+pub fn find_largest_word_box(hocr_content: &str, word: &str) -> Option<BoundingBox> {
+    let trim = |text: &str| text.trim_matches(|c: char| !c.is_alphanumeric()).to_string();
+    let target = trim(&clean_word(word));
+    if target.is_empty() {
+        return None;
+    }
+    
+    extract_word_bounding_boxes(hocr_content)
+        .into_iter()
+        .filter(|w| trim(&w.clean_text) == target)
+        .max_by(|a, b| {
+            let area = |w: &WordBox| (w.x2 - w.x1) * (w.y2 - w.y1);
+            area(a).total_cmp(&area(b))
+        })
+        .map(|w| BoundingBox::new(w.x1, w.y1, w.x2, w.y2))
+}
+
+/// Word boxes whose center lies inside the region (edges inclusive), in document order
+fn words_in_region(hocr_content: &str, x1: f64, y1: f64, x2: f64, y2: f64) -> Vec<WordBox> {
+    extract_word_bounding_boxes(hocr_content)
+        .into_iter()
+        .filter(|w| {
+            let (cx, cy) = ((w.x1 + w.x2) / 2.0, (w.y1 + w.y2) / 2.0);
+            cx >= x1 && cx <= x2 && cy >= y1 && cy <= y2
+        })
+        .collect()
+}
+
+/// Number of words whose center lies inside the region, for measuring how much
+/// text an annotation covers
+#[wasm_bindgen]
+pub fn word_count_in_region(hocr_content: &str, x1: f64, y1: f64, x2: f64, y2: f64) -> u32 {
+    words_in_region(hocr_content, x1, y1, x2, y2).len() as u32
+}
+
+/// Text of every word whose center lies inside the region, in reading order
+/// (lines top-to-bottom, words left-to-right), joined with single spaces
+#[wasm_bindgen]
+pub fn extract_text_in_region(hocr_content: &str, x1: f64, y1: f64, x2: f64, y2: f64) -> String {
+    let mut words = words_in_region(hocr_content, x1, y1, x2, y2);
+    
+    // Order lines by their top edge so out-of-order hOCR still reads top-to-bottom
+    let mut line_tops: HashMap<usize, f64> = HashMap::new();
+    for word in &words {
+        let top = line_tops.entry(word.line_index).or_insert(word.y1);
+        *top = top.min(word.y1);
+    }
+    words.sort_by(|a, b| {
+        line_tops[&a.line_index].total_cmp(&line_tops[&b.line_index])
+            .then(a.line_index.cmp(&b.line_index))
+            .then(a.x1.total_cmp(&b.x1))
+    });
+    
+    words.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" ")
+}
+
+/// One highlight box per line of a match, shaped like a text selection
+/// Returns `{x1, y1, x2, y2}` per line, top to bottom: the first line starts at the
+/// first matched word, the last ends at the last matched word and interior lines
+/// span the full width of the match
+#[wasm_bindgen]
+pub fn extract_line_boxes_for_match(hocr_content: &str, search_string: &str) -> js_sys::Array {
+    let results = js_sys::Array::new();
+    
+    if let Some(word_match) = match_word_boxes(hocr_content, search_string) {
+        for bbox in ragged_line_boxes(&group_words_into_line_boxes(&word_match.word_boxes)) {
+            let js_box = js_sys::Object::new();
+            js_sys::Reflect::set(&js_box, &"x1".into(), &bbox.x1.into()).unwrap();
+            js_sys::Reflect::set(&js_box, &"y1".into(), &bbox.y1.into()).unwrap();
+            js_sys::Reflect::set(&js_box, &"x2".into(), &bbox.x2.into()).unwrap();
+            js_sys::Reflect::set(&js_box, &"y2".into(), &bbox.y2.into()).unwrap();
+            results.push(&js_box);
+        }
+    }
+    
+    results
+}
+
+/// Fewest rectangles covering a match: like `extract_line_boxes_for_match`, but runs of
+/// consecutive full-width lines merge into one taller box while ragged first and last
+/// lines stay separate
+/// Returns `{x1, y1, x2, y2}` per rectangle, top to bottom
+#[wasm_bindgen]
+pub fn minimal_cover_boxes_for_match(hocr_content: &str, search_string: &str) -> js_sys::Array {
+    let results = js_sys::Array::new();
+    
+    if let Some(word_match) = match_word_boxes(hocr_content, search_string) {
+        let line_boxes = ragged_line_boxes(&group_words_into_line_boxes(&word_match.word_boxes));
+        for bbox in merge_full_width_lines(&line_boxes) {
+            let js_box = js_sys::Object::new();
+            js_sys::Reflect::set(&js_box, &"x1".into(), &bbox.x1.into()).unwrap();
+            js_sys::Reflect::set(&js_box, &"y1".into(), &bbox.y1.into()).unwrap();
+            js_sys::Reflect::set(&js_box, &"x2".into(), &bbox.x2.into()).unwrap();
+            js_sys::Reflect::set(&js_box, &"y2".into(), &bbox.y2.into()).unwrap();
+            results.push(&js_box);
+        }
+    }
+    
+    results
+}
+
+/// Every word box in the document, in reading order
+/// Returns `{text, x1, y1, x2, y2, lineIndex, color}`; `color` is null when the word
+/// has no explicit foreground color
+#[wasm_bindgen]
+pub fn extract_all_word_boxes(hocr_content: &str) -> js_sys::Array {
+    let results = js_sys::Array::new();
+    
+    for word in extract_word_bounding_boxes(hocr_content) {
+        let js_word = js_sys::Object::new();
+        js_sys::Reflect::set(&js_word, &"text".into(), &word.text.into()).unwrap();
+        js_sys::Reflect::set(&js_word, &"x1".into(), &word.x1.into()).unwrap();
+        js_sys::Reflect::set(&js_word, &"y1".into(), &word.y1.into()).unwrap();
+        js_sys::Reflect::set(&js_word, &"x2".into(), &word.x2.into()).unwrap();
+        js_sys::Reflect::set(&js_word, &"y2".into(), &word.y2.into()).unwrap();
+        js_sys::Reflect::set(&js_word, &"lineIndex".into(), &(word.line_index as u32).into()).unwrap();
+        js_sys::Reflect::set(&js_word, &"color".into(), &word.color.map_or(JsValue::NULL, JsValue::from)).unwrap();
+        results.push(&js_word);
+    }
+    
+    results
+}
+
+/// First `n` words of every line with their union box, as `(line_index, text, bbox)`
+/// in line order
+pub fn line_leaders(hocr_content: &str, n: usize) -> Vec<(usize, String, BoundingBox)> {
+    let mut lines: Vec<(usize, Vec<WordBox>)> = Vec::new();
+    for word in extract_word_bounding_boxes(hocr_content) {
+        match lines.last_mut() {
+            Some((line_index, words)) if *line_index == word.line_index => words.push(word),
+            _ => lines.push((word.line_index, vec![word])),
+        }
+    }
+    
+    lines.into_iter()
+        .filter_map(|(line_index, mut words)| {
+            words.truncate(n);
+            let bbox = calculate_bounding_box_from_words(&words)?;
+            let text = words.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" ");
+            Some((line_index, text, bbox))
+        })
+        .collect()
+}
+
+/// Leading words of each line, e.g. for a quick table of contents
+/// Returns `{lineIndex, text, x1, y1, x2, y2}` per line, the box covering the first `n` words
+#[wasm_bindgen]
+pub fn extract_line_leaders(hocr_content: &str, n: usize) -> js_sys::Array {
+    let results = js_sys::Array::new();
+    
+    for (line_index, text, bbox) in line_leaders(hocr_content, n) {
+        let js_leader = js_sys::Object::new();
+        js_sys::Reflect::set(&js_leader, &"lineIndex".into(), &(line_index as u32).into()).unwrap();
+        js_sys::Reflect::set(&js_leader, &"text".into(), &text.into()).unwrap();
+        js_sys::Reflect::set(&js_leader, &"x1".into(), &bbox.x1.into()).unwrap();
+        js_sys::Reflect::set(&js_leader, &"y1".into(), &bbox.y1.into()).unwrap();
+        js_sys::Reflect::set(&js_leader, &"x2".into(), &bbox.x2.into()).unwrap();
+        js_sys::Reflect::set(&js_leader, &"y2".into(), &bbox.y2.into()).unwrap();
+        results.push(&js_leader);
+    }
+    
+    results
+}
+
+/// Typical word height on the page: the mean height of the most common height bucket
+/// (`HEIGHT_BUCKET_PX` wide, smaller bucket on ties), so a few large headings don't skew it
+#[wasm_bindgen]
+pub fn dominant_word_height(hocr_content: &str) -> Option<f64> {
+    let mut buckets: HashMap<i64, Vec<f64>> = HashMap::new();
+    for word in extract_word_bounding_boxes(hocr_content) {
+        let height = word.y2 - word.y1;
+        buckets.entry((height / HEIGHT_BUCKET_PX).round() as i64).or_default().push(height);
+    }
+    
+    let (_, heights) = buckets.into_iter()
+        .max_by(|(bucket_a, a), (bucket_b, b)| a.len().cmp(&b.len()).then(bucket_b.cmp(bucket_a)))?;
+    Some(heights.iter().sum::<f64>() / heights.len() as f64)
+}
+
+/// Word-box heights counted in `bucket_size`-wide buckets, as `(bucket_start, count)`
+/// in ascending order; only populated buckets appear, and none for a non-positive size
+pub fn word_height_buckets(hocr_content: &str, bucket_size: f64) -> Vec<(f64, usize)> {
+    if bucket_size.is_nan() || bucket_size <= 0.0 {
+        return Vec::new();
+    }
+    
+    let mut counts: HashMap<i64, usize> = HashMap::new();
+    for word in extract_word_bounding_boxes(hocr_content) {
+        *counts.entry(((word.y2 - word.y1) / bucket_size).floor() as i64).or_default() += 1;
+    }
+    
+    let mut buckets: Vec<(f64, usize)> = counts.into_iter()
+        .map(|(bucket, count)| (bucket as f64 * bucket_size, count))
+        .collect();
+    buckets.sort_by(|a, b| a.0.total_cmp(&b.0));
+    buckets
+}
+
+/// Histogram of word heights, e.g. for telling headings from body text
+/// Returns `{bucketStart, count}` for each populated bucket, smallest heights first
+#[wasm_bindgen]
+pub fn word_height_histogram(hocr_content: &str, bucket_size: f64) -> js_sys::Array {
+    let results = js_sys::Array::new();
+    
+    for (bucket_start, count) in word_height_buckets(hocr_content, bucket_size) {
+        let js_bucket = js_sys::Object::new();
+        js_sys::Reflect::set(&js_bucket, &"bucketStart".into(), &bucket_start.into()).unwrap();
+        js_sys::Reflect::set(&js_bucket, &"count".into(), &(count as u32).into()).unwrap();
+        results.push(&js_bucket);
+    }
+    
+    results
+}
+
+/// Median distance between the tops of consecutive `ocr_line` boxes, in hOCR pixels
+/// Only downward steps count, so column jumps back to the top are ignored
+pub(crate) fn median_line_spacing(hocr_content: &str) -> Option<f64> {
+    let tops: Vec<f64> = find_elements(hocr_content, "span")
+        .iter()
+        .filter(|e| e.has_class("ocr_line"))
+        .filter_map(|line| parse_bbox_field(&line.title_fields()).map(|(_, y1, _, _)| y1))
+        .collect();
+    
+    let mut steps: Vec<f64> = tops.windows(2).map(|pair| pair[1] - pair[0]).filter(|&step| step > 0.0).collect();
+    if steps.is_empty() {
+        return None;
+    }
+    steps.sort_by(f64::total_cmp);
+    
+    let mid = steps.len() / 2;
+    Some(if steps.len().is_multiple_of(2) { (steps[mid - 1] + steps[mid]) / 2.0 } else { steps[mid] })
+}
+
+/// Rotation of an `ocr_line` in degrees, from a least-squares fit through its word-box
+/// centers; positive when the line descends to the right (hOCR y grows downward)
+/// None when the line has fewer than two words or they share one x center
+#[wasm_bindgen]
+pub fn estimate_line_skew(hocr_content: &str, line_index: usize) -> Option<f64> {
+    let centers: Vec<(f64, f64)> = extract_word_bounding_boxes(hocr_content)
+        .iter()
+        .filter(|w| w.line_index == line_index)
+        .map(|w| ((w.x1 + w.x2) / 2.0, (w.y1 + w.y2) / 2.0))
+        .collect();
+    if centers.len() < 2 {
+        return None;
+    }
+    
+    let n = centers.len() as f64;
+    let mean_x = centers.iter().map(|&(x, _)| x).sum::<f64>() / n;
+    let mean_y = centers.iter().map(|&(_, y)| y).sum::<f64>() / n;
+    let covariance: f64 = centers.iter().map(|&(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = centers.iter().map(|&(x, _)| (x - mean_x).powi(2)).sum();
+    if variance == 0.0 {
+        return None;
+    }
+    
+    Some((covariance / variance).atan().to_degrees())
+}
+
+/// Column x-ranges, left to right: word extents projected onto the x axis, split
+/// wherever an empty gap is wider than the median word height (word spacing is
+/// narrower than that, column gutters wider)
+pub fn column_ranges(hocr_content: &str) -> Vec<(f64, f64)> {
+    let word_boxes = extract_word_bounding_boxes(hocr_content);
+    if word_boxes.is_empty() {
+        return Vec::new();
+    }
+    
+    let mut heights: Vec<f64> = word_boxes.iter().map(|w| w.y2 - w.y1).collect();
+    heights.sort_by(f64::total_cmp);
+    let min_gap = heights[heights.len() / 2];
+    
+    let mut extents: Vec<(f64, f64)> = word_boxes.iter().map(|w| (w.x1, w.x2)).collect();
+    
+    extents.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut columns: Vec<(f64, f64)> = Vec::new();
+    for (x1, x2) in extents {
+        match columns.last_mut() {
+            Some(column) if x1 - column.1 <= min_gap => column.1 = column.1.max(x2),
+            _ => columns.push((x1, x2)),
+        }
+    }
+    
+    columns
+}
+
+/// Text columns on the page, e.g. for scoping a search to one column
+/// Returns `{columnIndex, xStart, xEnd}` per column, left to right
+#[wasm_bindgen]
+pub fn detect_columns(hocr_content: &str) -> js_sys::Array {
+    let results = js_sys::Array::new();
+    
+    for (column_index, (x_start, x_end)) in column_ranges(hocr_content).into_iter().enumerate() {
+        let js_column = js_sys::Object::new();
+        js_sys::Reflect::set(&js_column, &"columnIndex".into(), &(column_index as u32).into()).unwrap();
+        js_sys::Reflect::set(&js_column, &"xStart".into(), &x_start.into()).unwrap();
+        js_sys::Reflect::set(&js_column, &"xEnd".into(), &x_end.into()).unwrap();
+        results.push(&js_column);
+    }
+    
+    results
+}
+
+/// Width of the height buckets used by `dominant_word_height`, in hOCR pixels
+const HEIGHT_BUCKET_PX: f64 = 2.0;
+
+/// Build a text-selection shaped polygon around a match spanning one or more lines
+/// Returns an ordered array of `{x, y}` vertices: the first line starts at the first
+/// matched word, interior lines span the full width and the last line ends at the last
+/// matched word
+#[wasm_bindgen]
+pub fn extract_selection_polygon_for_match(hocr_content: &str, search_string: &str) -> js_sys::Array {
+    let vertices = js_sys::Array::new();
+    
+    if let Some(word_match) = match_word_boxes(hocr_content, search_string) {
+        let line_boxes = group_words_into_line_boxes(&word_match.word_boxes);
+        for (x, y) in selection_polygon(&line_boxes) {
+            let vertex = js_sys::Object::new();
+            js_sys::Reflect::set(&vertex, &"x".into(), &x.into()).unwrap();
+            js_sys::Reflect::set(&vertex, &"y".into(), &y.into()).unwrap();
+            vertices.push(&vertex);
+        }
+    }
+    
+    vertices
+}
+
+/// Extract bounding box coordinates for a matched string (legacy version using embedded text)
+/// Ported from Python extract_box.py logic - using word-level matching like Python
+#[wasm_bindgen] 
+pub fn extract_bounding_box(embedded_text: &str, closest_match_string: &str) -> Option<BoundingBox> {
+    if embedded_text.is_empty() || closest_match_string.is_empty() {
+        return None;
+    }
+    
+    // Split into words for matching (following Python approach)
+    let text_words: Vec<&str> = embedded_text.split_whitespace().collect();
+    let search_words: Vec<&str> = closest_match_string.split_whitespace().collect();
+    
+    if search_words.is_empty() {
+        return None;
+    }
+    
+    let search_length = search_words.len();
+    let mut best_similarity = 0.0;
+    let mut best_start_index = 0;
+    let mut best_end_index = 0;
+    
+    // Search from end to beginning as in the original Python code
+    for end_index in (search_length..=text_words.len()).rev() {
+        let start_index = end_index - search_length;
+        let window_words = &text_words[start_index..end_index];
+        
+        // Remove hOCR markers for comparison
+        let cleaned_window: Vec<String> = window_words.iter()
+            .filter(|word| !word.starts_with("[[") || !word.ends_with("]]"))
+            .map(|word| word.to_string())
+            .collect();
+        
+        if cleaned_window.len() >= search_length {
+            let window_text = cleaned_window.join(" ");
+            let similarity = calculate_string_similarity(&window_text, closest_match_string);
+            
+            if similarity > best_similarity {
+                best_similarity = similarity;
+                best_start_index = start_index;
+                best_end_index = end_index;
+                
+                if best_similarity > 0.85 {
+                    break;
+                }
+            }
+        }
+    }
+    
+    // No window scored (search longer than the text, or nothing in common)
+    if best_similarity == 0.0 {
+        return None;
+    }
+    
+    // Convert word indices back to byte offsets in the original text, from where each
+    // token really sits: markers and runs of whitespace or newlines between them would
+    // otherwise shift offsets rebuilt by re-joining words with single spaces
+    let token_offsets: Vec<(usize, usize)> = Regex::new(r"\S+").unwrap()
+        .find_iter(embedded_text)
+        .map(|token| (token.start(), token.end()))
+        .collect();
+    
+    let mut char_start_index = 0;
+    let mut char_end_index = embedded_text.len();
+    
+    if best_start_index > 0 {
+        char_start_index = token_offsets[best_start_index].0;
+    }
+    
+    if best_end_index < text_words.len() {
+        char_end_index = token_offsets[best_end_index - 1].1;
+    }
+    
+    // Extract LINE markers to calculate bounding box coordinates
+    let before_match = &embedded_text[..char_start_index];
+    let match_section = &embedded_text[char_start_index..char_end_index];
+    
+    // Find the last LINE marker before the match
+    let line_regex = Regex::new(r"\[\[LINE (\d+(?:\.\d+)?) (\d+(?:\.\d+)?) (\d+(?:\.\d+)?) (\d+(?:\.\d+)?)\]\]").unwrap();
+    
+    let mut x1 = 0.0;
+    let mut y1 = 0.0;
+    let mut y2 = 0.0;
+    
+    // Get starting coordinates from the line before the match
+    if let Some(caps) = line_regex.captures_iter(before_match).last() {
+        x1 = caps[1].parse().unwrap_or(0.0);
+        y1 = caps[2].parse().unwrap_or(0.0);
+    }
+    
+    // Get ending coordinates from lines within the match
+    // Following Python logic: find max x2 and final y2
+    let mut max_x2 = x1; // Initialize with starting x in case no matches
+    for caps in line_regex.captures_iter(match_section) {
+        let current_x2: f64 = caps[3].parse().unwrap_or(0.0);
+        let current_y2: f64 = caps[4].parse().unwrap_or(0.0);
+        
+        // Find maximum x2 (rightmost coordinate) across all lines
+        if current_x2 > max_x2 {
+            max_x2 = current_x2;
+        }
+        // Always update y2 to the last line's bottom coordinate
+        y2 = current_y2;
+    }
+    
+    let x2 = max_x2;
+    
+    // Return None if no valid coordinates found
+    if x1 == 0.0 && y1 == 0.0 && x2 == 0.0 && y2 == 0.0 {
+        return None;
+    }
+    
+    Some(BoundingBox::new(x1, y1, x2, y2))
+}
+
+/// Extract all word bounding boxes from hOCR content
+fn extract_word_bounding_boxes(hocr_content: &str) -> Vec<WordBox> {
+    let mut word_boxes = Vec::new();
+    
+    // Walk every span in document order; ocr_line spans advance the line counter
+    // and ocrx_word spans (including nested HTML tags) become word boxes
+    let mut lines_seen: usize = 0;
+    
+    for span in find_elements(hocr_content, "span") {
+        if span.has_class("ocr_line") {
+            lines_seen += 1;
+            continue;
+        }
+        if !span.has_class("ocrx_word") {
+            continue;
+        }
+        let line_index = lines_seen.saturating_sub(1);
+        
+        if let Some((x1, y1, x2, y2)) = parse_bbox_field(&span.title_fields()) {
+            // Some OCR engines emit corners in the wrong order; put them back
+            let (x1_val, x2_val) = (x1.min(x2), x1.max(x2));
+            let (y1_val, y2_val) = (y1.min(y2), y1.max(y2));
+            let raw_text = span.inner_html(hocr_content, "span");
+            
+            // Display text keeps its case; the matching form goes through clean_word
+            let clean_text_str = strip_word_markup(raw_text);
+            let clean_text_for_matching = clean_word(raw_text);
+            let redacted = span.has_class("ocr_redacted") || is_redaction_placeholder(&clean_text_str);
+            let superscript = has_opening_tag(raw_text, "sup");
+            let color = word_color(&span);
+            
+            if !clean_text_str.is_empty() && x1_val >= 0.0 && y1_val >= 0.0 && x2_val > x1_val && y2_val > y1_val {
+                word_boxes.push(WordBox {
+                    text: clean_text_str.clone(), // Store the clean text without HTML tags
+                    clean_text: clean_text_for_matching,
+                    x1: x1_val,
+                    y1: y1_val,
+                    x2: x2_val,
+                    y2: y2_val,
+                    line_index,
+                    redacted,
+                    superscript,
+                    color,
+                    source_span: (span.start, span.outer_end(hocr_content, "span")),
+                });
+            }
+        }
+    }
+    
+    mark_raised_superscripts(&mut word_boxes);
+    word_boxes
+}
+
+/// Foreground color of a word span, from its title (`fcolor`, `x_fcolor`) or inline style
+fn word_color(span: &HocrElement) -> Option<String> {
+    let fields = span.title_fields();
+    let from_title = fields.get("fcolor").or_else(|| fields.get("x_fcolor")).cloned();
+    
+    let from_style = || {
+        span.attributes.get("style")?
+            .split(';')
+            .filter_map(|declaration| declaration.split_once(':'))
+            .find(|(property, _)| property.trim().eq_ignore_ascii_case("color"))
+            .map(|(_, value)| value.trim().to_string())
+    };
+    
+    from_title.or_else(from_style).filter(|color| !color.is_empty())
+}
+
+/// Flag words that are small and sit well above their line's baseline
+/// A word counts when its bottom is more than a quarter of the line's median word
+/// height above the median bottom, and it is under 80% of that height
+fn mark_raised_superscripts(word_boxes: &mut [WordBox]) {
+    let median = |mut values: Vec<f64>| {
+        values.sort_by(f64::total_cmp);
+        values[values.len() / 2]
+    };
+    
+    let mut start = 0;
+    while start < word_boxes.len() {
+        let line_index = word_boxes[start].line_index;
+        let end = start + word_boxes[start..].iter().take_while(|w| w.line_index == line_index).count();
+        let line = &mut word_boxes[start..end];
+        
+        if line.len() > 1 {
+            let baseline = median(line.iter().map(|w| w.y2).collect());
+            let height = median(line.iter().map(|w| w.y2 - w.y1).collect());
+            for word in line.iter_mut() {
+                if baseline - word.y2 > 0.25 * height && word.y2 - word.y1 < 0.8 * height {
+                    word.superscript = true;
+                }
+            }
+        }
+        start = end;
+    }
+}
+
+/// Create embedded text from word boxes (like JS embedded text with LINE markers)
+fn create_embedded_text_from_word_boxes(word_boxes: &[WordBox]) -> String {
+    let mut embedded_text = String::from("[[PARAGRAPH]] ");
+    
+    // Add a LINE marker (using first and last word coordinates)
+    if !word_boxes.is_empty() {
+        let first_word = &word_boxes[0];
+        let last_word = &word_boxes[word_boxes.len() - 1];
+        embedded_text.push_str(&format!("[[LINE {} {} {} {}]] ", 
+            first_word.x1 as i32, first_word.y1 as i32, 
+            last_word.x2 as i32, last_word.y2 as i32));
+    }
+    
+    // Add the matching form of each word; display text stays on the word box
+    for word_box in word_boxes {
+        embedded_text.push_str(&word_box.clean_text);
+        embedded_text.push(' ');
+    }
+    
+    embedded_text
+}
+
+/// Exact copy of JS TextMatcher.findClosestMatch algorithm
+fn find_js_style_match(embedded_text: &str, search_string: &str, word_boxes: &[WordBox]) -> Option<WordBoxMatch> {
+    log!("WASM: JS-style matching '{}' in embedded text", search_string);
+    
+    if embedded_text.is_empty() || search_string.is_empty() {
+        return None;
+    }
+    
+    // Clean the embedded text by removing hOCR markers (exact JS logic)
+    let regex = Regex::new(r"\[\[.*?\]\] ").unwrap();
+    let cleaned_text = regex.replace_all(embedded_text, "").to_string();
+    let cleaned_words: Vec<&str> = cleaned_text.split_whitespace().filter(|w| !w.is_empty()).collect();
+    let cleaned_search = clean_word(search_string);
+    let search_words: Vec<&str> = cleaned_search.split_whitespace().filter(|w| !w.is_empty()).collect();
+    
+    log!("WASM: Cleaned text has {} words", cleaned_words.len());
+    log!("WASM: Search has {} words", search_words.len());
+    log!("WASM: Search words: {:?}", search_words);
+    
+    if search_words.is_empty() {
+        return None;
+    }
+    
+    let window_size = search_words.len();
+    let mut best_cleaned_start_index = 0;
+    let mut best_similarity = 0.0;
+    
+    // Sliding window approach (exact JS logic)
+    if window_size <= cleaned_words.len() {
+        for i in 0..=(cleaned_words.len() - window_size) {
+            let window_words = &cleaned_words[i..i + window_size];
+            
+            // Calculate sequence similarity (exact JS logic)
+            let similarity = js_sequence_similarity(window_words, &search_words);
+            
+            if similarity > best_similarity {
+                best_similarity = similarity;
+                best_cleaned_start_index = i;
+                
+                // Early exit for perfect match (exact JS logic)
+                if similarity >= 0.95 {
+                    log!("WASM: Perfect match found at position {}", i);
+                    break;
+                }
+            }
+        }
+    }
+    
+    log!("WASM: Best match: cleaned_start={}, similarity={:.3}", best_cleaned_start_index, best_similarity);
+    
+    // Only proceed if we have a reasonable similarity (copying JS threshold logic)
+    if best_similarity <= 0.0 {
+        log!("WASM: No match found - similarity is 0");
+        return None;
+    }
+    
+    // Windows scoring exactly as well later on are equally good textually; the one whose
+    // words sit closest together on the page is the likelier real phrase
+    let tied_starts: Vec<usize> = (best_cleaned_start_index + 1..=cleaned_words.len() - window_size)
+        .filter(|&i| js_sequence_similarity(&cleaned_words[i..i + window_size], &search_words) == best_similarity)
+        .collect();
+    
+    // Map cleaned text indices back to word boxes using the exact JS algorithm
+    let alignment = align_boxes_to_cleaned_words(&cleaned_words, word_boxes);
+    if !tied_starts.is_empty() {
+        let coherence_at = |start: usize| {
+            geometric_coherence(word_boxes, &box_indices_in_range(&alignment, start, start + window_size))
+        };
+        let mut best_coherence = coherence_at(best_cleaned_start_index);
+        for start in tied_starts {
+            let coherence = coherence_at(start);
+            if coherence > best_coherence {
+                best_coherence = coherence;
+                best_cleaned_start_index = start;
+            }
+        }
+        log!("WASM: Tie broken by compactness: cleaned_start={}", best_cleaned_start_index);
+    }
+    
+    let best_cleaned_end_index = best_cleaned_start_index + window_size;
+    let result_indices = box_indices_in_range(&alignment, best_cleaned_start_index, best_cleaned_end_index);
+    
+    log!("WASM: Found {} word boxes for match", result_indices.len());
+    WordBoxMatch::from_indices(word_boxes, &result_indices, best_similarity)
+}
+
+/// Indices of the word boxes overlapping the cleaned-word range `[start, end)`
+/// Boxes are aligned to cleaned words in order; a box whose words don't line up is skipped
+fn box_indices_for_cleaned_range(cleaned_words: &[&str], word_boxes: &[WordBox], start: usize, end: usize) -> Vec<usize> {
+    box_indices_in_range(&align_boxes_to_cleaned_words(cleaned_words, word_boxes), start, end)
+}
+
+/// `(box_index, cleaned_start, cleaned_end)` for every word box whose words line up with
+/// the cleaned words, in order
+fn align_boxes_to_cleaned_words(cleaned_words: &[&str], word_boxes: &[WordBox]) -> Vec<(usize, usize, usize)> {
+    // The key insight: we need to find which word boxes correspond to the cleaned word indices
+    // Since the word boxes are extracted in order, we need to map the cleaned word positions
+    // back to the original word box positions
+    
+    // Create a mapping from word box text to cleaned word positions
+    let mut word_box_to_cleaned_index = Vec::new();
+    let mut cleaned_word_index = 0;
+    
+    // Debug: show first few word boxes and cleaned words
+    log!("WASM: First 10 word boxes: {:?}", 
+        word_boxes.iter().take(10).map(|wb| &wb.text).collect::<Vec<_>>());
+    log!("WASM: First 10 cleaned words: {:?}", 
+        cleaned_words.iter().take(10).collect::<Vec<_>>());
+    
+    for (box_index, word_box) in word_boxes.iter().enumerate() {
+        // A box's clean_text may hold several cleaned words; compare them all
+        let box_words: Vec<&str> = word_box.clean_text.split_whitespace().collect();
+        let box_end = cleaned_word_index + box_words.len();
+        
+        if box_end <= cleaned_words.len() && cleaned_words[cleaned_word_index..box_end] == box_words[..] {
+            word_box_to_cleaned_index.push((box_index, cleaned_word_index, box_end));
+            
+            if box_index < 20 {  // Debug first few matches
+                log!("WASM: Match {}: box[{}]='{}' -> cleaned[{}..{}]", 
+                    word_box_to_cleaned_index.len() - 1, box_index, word_box.text, cleaned_word_index, box_end);
+            }
+            cleaned_word_index = box_end;
+        } else if box_index < 20 {  // Debug first few non-matches
+            log!("WASM: NO match: box[{}]='{}' (clean='{}') vs cleaned[{}]", 
+                box_index, word_box.text, word_box.clean_text, cleaned_word_index);
+        }
+    }
+    
+    log!("WASM: Mapped {} word boxes to cleaned positions", word_box_to_cleaned_index.len());
+    word_box_to_cleaned_index
+}
+
+/// Indices of the aligned boxes overlapping the cleaned-word range `[start, end)`
+fn box_indices_in_range(alignment: &[(usize, usize, usize)], start: usize, end: usize) -> Vec<usize> {
+    alignment.iter()
+        .filter(|&&(_, box_start, box_end)| box_start < end && box_end > start)
+        .map(|&(box_index, _, _)| box_index)
+        .collect()
+}
+
+/// Exact copy of JS sequenceSimilarity algorithm
+fn js_sequence_similarity(seq1: &[&str], seq2: &[&str]) -> f64 {
+    let max_length = std::cmp::max(seq1.len(), seq2.len());
+    if max_length == 0 {
+        return 1.0;
+    }
+    
+    let matches = seq1.iter()
+        .zip(seq2.iter())
+        .filter(|(a, b)| a == b)
+        .count();
+    
+    matches as f64 / max_length as f64
+}
+
+/// `js_sequence_similarity` with each search word weighted by `1 / ln(1 + freq)`, where
+/// `freq` is its count on the page (at least 1), so rare words dominate the score
+fn idf_sequence_similarity(window: &[&str], search_words: &[&str], frequencies: &HashMap<&str, usize>) -> f64 {
+    let weight = |word: &str| 1.0 / (1.0 + frequencies.get(word).copied().unwrap_or(0).max(1) as f64).ln();
+    
+    let total: f64 = search_words.iter().map(|w| weight(w)).sum();
+    if total == 0.0 {
+        return 0.0;
+    }
+    let matched: f64 = window.iter()
+        .zip(search_words.iter())
+        .filter(|(a, b)| a == b)
+        .map(|(_, b)| weight(b))
+        .sum();
+    
+    matched / total
+}
+
+/// Simple string similarity for legacy function
+fn calculate_string_similarity(s1: &str, s2: &str) -> f64 {
+    let chars1 = grapheme_clusters(s1);
+    let chars2 = grapheme_clusters(s2);
+    
+    let matching = chars1.iter()
+        .zip(chars2.iter())
+        .filter(|(a, b)| a == b)
+        .count();
+    
+    let max_len = std::cmp::max(chars1.len(), chars2.len());
+    if max_len == 0 {
+        1.0
+    } else {
+        matching as f64 / max_len as f64
+    }
+}
+
+/// Group consecutive word boxes into visual lines (by vertical overlap) and return
+/// the union box of each line, top to bottom
+fn group_words_into_line_boxes(word_boxes: &[WordBox]) -> Vec<BoundingBox> {
+    let mut line_boxes: Vec<BoundingBox> = Vec::new();
+    
+    for word in word_boxes {
+        let center_y = (word.y1 + word.y2) / 2.0;
+        match line_boxes.last_mut() {
+            Some(line) if center_y >= line.y1 && center_y <= line.y2 => {
+                line.x1 = line.x1.min(word.x1);
+                line.y1 = line.y1.min(word.y1);
+                line.x2 = line.x2.max(word.x2);
+                line.y2 = line.y2.max(word.y2);
+            }
+            _ => line_boxes.push(BoundingBox::new(word.x1, word.y1, word.x2, word.y2)),
+        }
+    }
+    
+    line_boxes
+}
+
+/// Split word boxes into runs that share a visual line, using the same vertical-center
+/// rule as `group_words_into_line_boxes`
+fn split_visual_lines(word_boxes: Vec<WordBox>) -> Vec<Vec<WordBox>> {
+    let mut lines: Vec<(BoundingBox, Vec<WordBox>)> = Vec::new();
+    
+    for word in word_boxes {
+        let center_y = (word.y1 + word.y2) / 2.0;
+        match lines.last_mut() {
+            Some((extent, words)) if center_y >= extent.y1 && center_y <= extent.y2 => {
+                extent.y1 = extent.y1.min(word.y1);
+                extent.y2 = extent.y2.max(word.y2);
+                words.push(word);
+            }
+            _ => lines.push((BoundingBox::new(word.x1, word.y1, word.x2, word.y2), vec![word])),
+        }
+    }
+    
+    lines.into_iter().map(|(_, words)| words).collect()
+}
+
+/// Per-line highlight boxes with ragged ends: the first line starts at the first
+/// matched word, the last line ends at the last matched word and every line
+/// otherwise spans the full width of the match
+fn ragged_line_boxes(line_boxes: &[BoundingBox]) -> Vec<BoundingBox> {
+    let left = line_boxes.iter().map(|b| b.x1).fold(f64::INFINITY, f64::min);
+    let right = line_boxes.iter().map(|b| b.x2).fold(f64::NEG_INFINITY, f64::max);
+    let last = line_boxes.len().saturating_sub(1);
+    
+    line_boxes.iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let x1 = if i == 0 { line.x1 } else { left };
+            let x2 = if i == last { line.x2 } else { right };
+            BoundingBox::new(x1, line.y1, x2, line.y2)
+        })
+        .collect()
+}
+
+/// Merge vertically consecutive line boxes with identical left and right edges (the
+/// full-width interior lines of `ragged_line_boxes`) into single boxes
+fn merge_full_width_lines(line_boxes: &[BoundingBox]) -> Vec<BoundingBox> {
+    let mut merged: Vec<BoundingBox> = Vec::new();
+    
+    for line in line_boxes {
+        match merged.last_mut() {
+            Some(previous) if previous.x1 == line.x1 && previous.x2 == line.x2 => {
+                previous.y2 = previous.y2.max(line.y2);
+            }
+            _ => merged.push(line.clone()),
+        }
+    }
+    
+    merged
+}
+
+/// Compute the outline of a text selection covering the given line boxes
+fn selection_polygon(line_boxes: &[BoundingBox]) -> Vec<(f64, f64)> {
+    let (first, last) = match (line_boxes.first(), line_boxes.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Vec::new(),
+    };
+    
+    if line_boxes.len() == 1 {
+        return vec![
+            (first.x1, first.y1),
+            (first.x2, first.y1),
+            (first.x2, first.y2),
+            (first.x1, first.y2),
+        ];
+    }
+    
+    let left = line_boxes.iter().map(|b| b.x1).fold(f64::INFINITY, f64::min);
+    let right = line_boxes.iter().map(|b| b.x2).fold(f64::NEG_INFINITY, f64::max);
+    
+    // Walk clockwise: ragged start of the first line, the right edge down to the
+    // last line, its ragged end, then back up the left edge
+    let raw = [
+        (first.x1, first.y1),
+        (right, first.y1),
+        (right, last.y1),
+        (last.x2, last.y1),
+        (last.x2, last.y2),
+        (left, last.y2),
+        (left, first.y2),
+        (first.x1, first.y2),
+    ];
+    
+    // Drop repeated points, then points lying on a straight edge
+    let mut points: Vec<(f64, f64)> = Vec::new();
+    for point in raw {
+        if points.last() != Some(&point) && (points.is_empty() || point != points[0]) {
+            points.push(point);
+        }
+    }
+    
+    let mut vertices = Vec::new();
+    for i in 0..points.len() {
+        let prev = points[(i + points.len() - 1) % points.len()];
+        let next = points[(i + 1) % points.len()];
+        let point = points[i];
+        let collinear = (prev.0 == point.0 && point.0 == next.0) || (prev.1 == point.1 && point.1 == next.1);
+        if !collinear {
+            vertices.push(point);
+        }
+    }
+    
+    vertices
+}
+
+/// How tightly the words at `indices` cluster: total word area over the area of their
+/// union box; 1.0 for words that tile their box, approaching 0 for scattered words
+fn geometric_coherence(word_boxes: &[WordBox], indices: &[usize]) -> f64 {
+    let words = indices.iter().map(|&i| &word_boxes[i]);
+    let (min_x, min_y, max_x, max_y, word_area) = words.fold(
+        (f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY, 0.0),
+        |(min_x, min_y, max_x, max_y, area), w| {
+            (min_x.min(w.x1), min_y.min(w.y1), max_x.max(w.x2), max_y.max(w.y2), area + (w.x2 - w.x1) * (w.y2 - w.y1))
+        },
+    );
+    let union_area = (max_x - min_x) * (max_y - min_y);
+    if indices.is_empty() || union_area <= 0.0 {
+        return 0.0;
+    }
+    
+    word_area / union_area
+}
+
+/// Left column, then right column, each in document order; the column split is the
+/// page center, or the center of the word extents when the page width is unknown
+fn order_two_columns(word_boxes: Vec<WordBox>, page_width: Option<f64>) -> Vec<WordBox> {
+    let center = page_width.map(|width| width / 2.0).unwrap_or_else(|| {
+        let left = word_boxes.iter().map(|w| w.x1).fold(f64::INFINITY, f64::min);
+        let right = word_boxes.iter().map(|w| w.x2).fold(f64::NEG_INFINITY, f64::max);
+        (left + right) / 2.0
+    });
+    
+    let (mut left, right): (Vec<WordBox>, Vec<WordBox>) = word_boxes
+        .into_iter()
+        .partition(|w| (w.x1 + w.x2) / 2.0 < center);
+    left.extend(right);
+    left
+}
+
+/// Text made up solely of Unicode block elements such as `█████`
+fn is_redaction_placeholder(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| ('\u{2580}'..='\u{259F}').contains(&c))
+}
+
+/// True for words made up solely of punctuation or symbols
+fn is_punctuation_only(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| !c.is_alphanumeric() && !c.is_whitespace())
+}
+
+/// Calculate bounding box from a list of word boxes
+fn calculate_bounding_box_from_words(word_boxes: &[WordBox]) -> Option<BoundingBox> {
+    if word_boxes.is_empty() {
+        return None;
+    }
+    
+    // Find the minimum and maximum coordinates
+    let min_x = word_boxes.iter().map(|w| w.x1).fold(f64::INFINITY, f64::min);
+    let min_y = word_boxes.iter().map(|w| w.y1).fold(f64::INFINITY, f64::min);
+    let max_x = word_boxes.iter().map(|w| w.x2).fold(f64::NEG_INFINITY, f64::max);
+    let max_y = word_boxes.iter().map(|w| w.y2).fold(f64::NEG_INFINITY, f64::max);
+    
+    Some(BoundingBox::new(min_x, min_y, max_x, max_y))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_embedded_text() {
+        let hocr_sample = r#"
+        <p class='ocr_par'>
+            <span class='ocr_line' title='bbox 100 200 300 400'>
+                <span class='ocrx_word'>Hello</span>
+                <span class='ocrx_word'>World</span>
+            </span>
+        </p>"#;
+        
+        let result = extract_embedded_text_from_hocr(hocr_sample);
+        assert!(result.contains("[[PARAGRAPH]]"));
+        assert!(result.contains("[[LINE 100 200 300 400]]"));
+        assert!(result.contains("Hello"));
+        assert!(result.contains("World"));
+    }
+
+    #[test]
+    fn test_find_hocr_match_reports_similarity() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 100 200 500 240'>
+            <span class='ocrx_word' title='bbox 100 200 180 240'>Hello</span>
+            <span class='ocrx_word' title='bbox 190 200 300 240'>World</span>
+            <span class='ocrx_word' title='bbox 310 200 500 240'>again</span>
+        </span>"#;
+        
+        let result = find_hocr_match(hocr_sample, "Hello World", &ExtractOptions::default()).unwrap();
+        assert_eq!(result.similarity, 1.0);
+        assert_eq!(result.matched_text, "Hello World");
+        assert_eq!(result.word_count, 2);
+        assert_eq!((result.bbox.x1, result.bbox.y1, result.bbox.x2, result.bbox.y2), (100.0, 200.0, 300.0, 240.0));
+    }
+
+    #[test]
+    fn test_selection_polygon_for_two_lines() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 100 100 470 130'>
+            <span class='ocrx_word' title='bbox 100 100 280 130'>The</span>
+            <span class='ocrx_word' title='bbox 300 100 380 130'>quick</span>
+            <span class='ocrx_word' title='bbox 390 100 470 130'>brown</span>
+        </span>
+        <span class='ocr_line' title='bbox 100 140 480 170'>
+            <span class='ocrx_word' title='bbox 100 140 160 170'>fox</span>
+            <span class='ocrx_word' title='bbox 170 140 480 170'>jumps</span>
+        </span>"#;
+        
+        let word_match = match_word_boxes(hocr_sample, "quick brown fox jumps").unwrap();
+        let line_boxes = group_words_into_line_boxes(&word_match.word_boxes);
+        assert_eq!(line_boxes.len(), 2);
+        
+        let polygon = selection_polygon(&line_boxes);
+        assert_eq!(polygon, vec![
+            (300.0, 100.0),
+            (480.0, 100.0),
+            (480.0, 170.0),
+            (100.0, 170.0),
+            (100.0, 130.0),
+            (300.0, 130.0),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_title_fields_any_order() {
+        let fields = parse_title_fields("ppageno 2; rotate 90; bbox 0 0 2550 3300; scan_res 300 300");
+        assert_eq!(fields.get("ppageno").map(String::as_str), Some("2"));
+        assert_eq!(fields.get("scan_res").map(String::as_str), Some("300 300"));
+        assert_eq!(parse_bbox_field(&fields), Some((0.0, 0.0, 2550.0, 3300.0)));
+    }
+
+    #[test]
+    fn test_extract_page_dimensions_multi_field_title() {
+        let hocr_sample = r#"<div class='ocr_page' id='page_2' title='ppageno 2; rotate 90; bbox 0 0 2550 3300'>
+            <span class='ocrx_word' title='x_wconf 95; bbox 10 20 60 40'>Hello</span>
+        </div>"#;
+        
+        let page = extract_page_dimensions(hocr_sample).unwrap();
+        assert_eq!(page.width, 2550.0);
+        assert_eq!(page.height, 3300.0);
+        assert_eq!(page.rotation, 90.0);
+        
+        let words = extract_word_bounding_boxes(hocr_sample);
+        assert_eq!(words.len(), 1);
+        assert_eq!((words[0].x1, words[0].y1, words[0].x2, words[0].y2), (10.0, 20.0, 60.0, 40.0));
+    }
+
+    #[test]
+    fn test_clean_word() {
+        assert_eq!(clean_word("  <strong>Smith&amp;Co</strong>&#44; "), "smith&co,");
+        assert_eq!(clean_word("<em>caf&#xE9;</em>"), "café");
+        assert_eq!(clean_word("AT&T"), "at&t");
+        assert_eq!(strip_word_markup("<b>Hello</b>"), "Hello");
+    }
+
+    #[test]
+    fn test_extract_match_line_index() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 100 100 400 130'>
+            <span class='ocrx_word' title='bbox 100 100 200 130'>First</span>
+            <span class='ocrx_word' title='bbox 210 100 400 130'>line</span>
+        </span>
+        <span class='ocr_line' title='bbox 100 140 400 170'>
+            <span class='ocrx_word' title='bbox 100 140 200 170'>second</span>
+            <span class='ocrx_word' title='bbox 210 140 400 170'>phrase</span>
+        </span>"#;
+        
+        assert_eq!(extract_match_line_index(hocr_sample, "second phrase"), Some(1));
+        assert_eq!(extract_match_line_index(hocr_sample, "First line"), Some(0));
+    }
+
+    #[test]
+    fn test_mixed_case_ocr_maps_to_display_text() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 600 40'>
+            <span class='ocrx_word' title='bbox 0 0 90 40'>The</span>
+            <span class='ocrx_word' title='bbox 100 0 250 40'>New York</span>
+            <span class='ocrx_word' title='bbox 260 0 380 40'>QUICK</span>
+            <span class='ocrx_word' title='bbox 390 0 500 40'>Brown</span>
+        </span>"#;
+        
+        let result = find_hocr_match(hocr_sample, "quick brown", &ExtractOptions::default()).unwrap();
+        assert_eq!(result.similarity, 1.0);
+        assert_eq!(result.matched_text, "QUICK Brown");
+        assert_eq!((result.bbox.x1, result.bbox.x2), (260.0, 500.0));
+    }
+
+    #[test]
+    fn test_trim_trailing_punct_excludes_period_box() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 500 40'>
+            <span class='ocrx_word' title='bbox 0 0 90 40'>end</span>
+            <span class='ocrx_word' title='bbox 100 0 150 40'>of</span>
+            <span class='ocrx_word' title='bbox 160 0 380 40'>sentence</span>
+            <span class='ocrx_word' title='bbox 420 0 440 40'>.</span>
+        </span>"#;
+        
+        let untrimmed = find_hocr_match(hocr_sample, "of sentence .", &ExtractOptions::default()).unwrap();
+        assert_eq!(untrimmed.bbox.x2, 440.0);
+        
+        let mut options = ExtractOptions::new();
+        options.trim_trailing_punct = true;
+        let trimmed = find_hocr_match(hocr_sample, "of sentence .", &options).unwrap();
+        assert_eq!(trimmed.bbox.x2, 380.0);
+        assert_eq!(trimmed.matched_text, "of sentence .");
+        assert_eq!(trimmed.word_count, 3);
+    }
+
+    #[test]
+    fn test_hocr_document_reuses_parse() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 500 40'>
+            <span class='ocrx_word' title='bbox 0 0 90 40'>Invoice</span>
+            <span class='ocrx_word' title='bbox 100 0 200 40'>number</span>
+            <span class='ocrx_word' title='bbox 210 0 300 40'>total</span>
+            <span class='ocrx_word' title='bbox 310 0 400 40'>due</span>
+        </span>"#;
+        
+        let document = HocrDocument::new(hocr_sample);
+        assert_eq!(document.word_count(), 4);
+        
+        let first = document.find_box("invoice number").unwrap();
+        assert_eq!((first.x1, first.x2), (0.0, 200.0));
+        
+        let second = document.find_box("total due").unwrap();
+        assert_eq!((second.x1, second.x2), (210.0, 400.0));
+    }
+
+    #[test]
+    fn test_extract_embedded_text_without_paragraphs() {
+        let hocr_sample = r#"
+        <div class='ocr_page' title='bbox 0 0 1000 1000'>
+            <span class='ocr_line' title='bbox 100 200 300 400'>
+                <span class='ocrx_word' title='bbox 100 200 180 400'>Hello</span>
+                <span class='ocrx_word' title='bbox 190 200 300 400'>World</span>
+            </span>
+        </div>"#;
+        
+        let result = extract_embedded_text_from_hocr(hocr_sample);
+        assert_eq!(result, "[[PARAGRAPH]] [[LINE 100 200 300 400]] Hello World");
+        assert_eq!(extract_embedded_text_from_hocr("<div class='ocr_page'></div>"), "");
+    }
+
+    #[test]
+    fn test_average_matched_word_height() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 500 40'>
+            <span class='ocrx_word' title='bbox 0 0 90 20'>tiny</span>
+            <span class='ocrx_word' title='bbox 100 0 200 30'>medium</span>
+            <span class='ocrx_word' title='bbox 210 0 300 40'>large</span>
+        </span>"#;
+        
+        assert_eq!(average_matched_word_height(hocr_sample, "tiny medium large"), Some(30.0));
+        assert_eq!(average_matched_word_height(hocr_sample, "medium large"), Some(35.0));
+        assert_eq!(average_matched_word_height(hocr_sample, "absent"), None);
+    }
+
+    #[test]
+    fn test_word_attributes_in_any_order_and_quoting() {
+        let hocr_sample = r#"
+        <span title="bbox 100 200 180 240" class='ocr_line'>
+            <span title="bbox 100 200 180 240; x_wconf 96" id='word_1' class='ocrx_word'>Hello</span>
+            <span class="ocrx_word" title='bbox 190 200 300 240'>World</span>
+            <span class='ocrx_word' title='bbox 310 200 400 240'/>
+            <span id="w4" class="ocrx_word" title="image 'a.png'; bbox 410 200 500 240">again</span>
+        </span>"#;
+        
+        let words = extract_word_bounding_boxes(hocr_sample);
+        let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, vec!["Hello", "World", "again"]);
+        assert_eq!((words[0].x1, words[0].x2), (100.0, 180.0));
+        assert_eq!((words[2].x1, words[2].x2), (410.0, 500.0));
+        assert!(words.iter().all(|w| w.line_index == 0));
+    }
+
+    #[test]
+    fn test_locate_bounding_box_error_codes() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 500 40'>
+            <span class='ocrx_word' title='bbox 0 0 90 40'>alpha</span>
+            <span class='ocrx_word' title='bbox 100 0 200 40'>beta</span>
+        </span>"#;
+        
+        assert_eq!(locate_bounding_box("", "alpha", None).unwrap_err(), MatchError::EmptyInput);
+        assert_eq!(locate_bounding_box(hocr_sample, "", None).unwrap_err(), MatchError::EmptyInput);
+        assert_eq!(locate_bounding_box(hocr_sample, " \t ", None).unwrap_err(), MatchError::BlankSearch);
+        assert_eq!(locate_bounding_box("<p>no words</p>", "alpha", None).unwrap_err(), MatchError::NoWordBoxes);
+        assert_eq!(locate_bounding_box(hocr_sample, "gamma", None).unwrap_err(), MatchError::NoMatch);
+        assert_eq!(locate_bounding_box(hocr_sample, "alpha delta", Some(0.9)).unwrap_err(), MatchError::BelowThreshold);
+        
+        let bbox = locate_bounding_box(hocr_sample, "alpha beta", Some(0.9)).unwrap();
+        assert_eq!((bbox.x1, bbox.x2), (0.0, 200.0));
+        assert_eq!(MatchError::NoMatch.code(), "NoMatch");
+    }
+
+    #[test]
+    fn test_find_largest_word_box_prefers_heading() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 300 20'>
+            <span class='ocrx_word' title='bbox 0 0 80 20'>see</span>
+            <span class='ocrx_word' title='bbox 90 0 180 20'>Introduction</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 100 600 180'>
+            <span class='ocrx_word' title='bbox 0 100 600 180'>INTRODUCTION:</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 200 300 220'>
+            <span class='ocrx_word' title='bbox 0 200 90 220'>introduction</span>
+        </span>"#;
+        
+        let bbox = find_largest_word_box(hocr_sample, "Introduction").unwrap();
+        assert_eq!((bbox.x1, bbox.y1, bbox.x2, bbox.y2), (0.0, 100.0, 600.0, 180.0));
+        assert!(find_largest_word_box(hocr_sample, "conclusion").is_none());
+        assert!(find_largest_word_box(hocr_sample, "  ").is_none());
+    }
+
+    #[test]
+    fn test_extract_text_in_region() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 100 400 140'>
+            <span class='ocrx_word' title='bbox 210 100 300 140'>Total</span>
+            <span class='ocrx_word' title='bbox 310 100 400 140'>Due</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 0 400 40'>
+            <span class='ocrx_word' title='bbox 0 0 90 40'>Invoice</span>
+        </span>"#;
+        
+        assert_eq!(extract_text_in_region(hocr_sample, 200.0, 90.0, 500.0, 150.0), "Total Due");
+        assert_eq!(extract_text_in_region(hocr_sample, 0.0, 0.0, 260.0, 150.0), "Invoice Total");
+        assert_eq!(extract_text_in_region(hocr_sample, 500.0, 500.0, 600.0, 600.0), "");
+    }
+
+    #[test]
+    fn test_match_each_page_reports_pages_with_phrase() {
+        let page = |title: &str, words: &[&str]| {
+            let spans: String = words.iter()
+                .enumerate()
+                .map(|(i, w)| format!("<span class='ocrx_word' title='bbox {} 10 {} 40'>{}</span>", i * 100, i * 100 + 90, w))
+                .collect();
+            format!("<div class='ocr_page' title='bbox 0 0 1000 1000'><span class='ocr_line' title='{}'>{}</span></div>", title, spans)
+        };
+        let hocr_sample = [
+            page("bbox 0 10 500 40", &["Quarterly", "Report", "Acme"]),
+            page("bbox 0 10 500 40", &["unrelated", "body", "text"]),
+            page("bbox 0 10 500 40", &["Acme", "Quarterly", "Report"]),
+        ].concat();
+        
+        assert_eq!(split_pages(&hocr_sample).len(), 3);
+        let matches = match_each_page(&hocr_sample, "Quarterly Report", 0.9);
+        let pages: Vec<usize> = matches.iter().map(|(page, _)| *page).collect();
+        assert_eq!(pages, vec![0, 2]);
+        assert_eq!((matches[0].1.bbox.x1, matches[0].1.bbox.x2), (0.0, 190.0));
+        assert_eq!((matches[1].1.bbox.x1, matches[1].1.bbox.x2), (100.0, 290.0));
+    }
+
+    #[test]
+    fn test_skip_redacted_bridges_phrase() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 600 40'>
+            <span class='ocrx_word' title='bbox 0 0 90 40'>Payment</span>
+            <span class='ocrx_word' title='bbox 100 0 190 40'>to</span>
+            <span class='ocrx_word' title='bbox 200 0 290 40'>██████</span>
+            <span class='ocrx_word' title='bbox 300 0 390 40'>Smith</span>
+            <span class='ocrx_word ocr_redacted' title='bbox 400 0 490 40'>xxxx</span>
+            <span class='ocrx_word' title='bbox 500 0 590 40'>Jones</span>
+        </span>"#;
+        
+        let plain = find_hocr_match(hocr_sample, "to Smith Jones", &ExtractOptions::default()).unwrap();
+        assert!(plain.similarity < 1.0);
+        
+        let mut options = ExtractOptions::new();
+        options.skip_redacted = true;
+        let result = find_hocr_match(hocr_sample, "to Smith Jones", &options).unwrap();
+        assert_eq!(result.similarity, 1.0);
+        assert_eq!(result.matched_text, "to Smith Jones");
+        assert_eq!((result.bbox.x1, result.bbox.x2), (100.0, 590.0));
+    }
+
+    #[test]
+    fn test_clamp_to_page_limits_box() {
+        let hocr_sample = r#"
+        <div class='ocr_page' title='image "scan.png"; bbox 0 0 1000 800'>
+        <span class='ocr_line' title='bbox 800 760 1100 830'>
+            <span class='ocrx_word' title='bbox 800 760 950 820'>Page</span>
+            <span class='ocrx_word' title='bbox 960 760 1100 830'>total</span>
+        </span>
+        </div>"#;
+        
+        let unclamped = extract_bounding_box_from_hocr(hocr_sample, "Page total").unwrap();
+        assert_eq!((unclamped.x2, unclamped.y2), (1100.0, 830.0));
+        
+        let mut options = ExtractOptions::new();
+        options.clamp_to_page = true;
+        let bbox = extract_bounding_box_from_hocr_with_options(hocr_sample, "Page total", &options).unwrap();
+        assert_eq!((bbox.x1, bbox.y1, bbox.x2, bbox.y2), (800.0, 760.0, 1000.0, 800.0));
+    }
+
+    #[test]
+    fn test_match_all_occurrences_returns_each_phrase() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 600 40'>
+            <span class='ocrx_word' title='bbox 0 0 90 40'>Net</span>
+            <span class='ocrx_word' title='bbox 100 0 190 40'>amount</span>
+            <span class='ocrx_word' title='bbox 200 0 290 40'>due</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 100 600 140'>
+            <span class='ocrx_word' title='bbox 0 100 90 140'>Gross</span>
+            <span class='ocrx_word' title='bbox 100 100 190 140'>amount</span>
+            <span class='ocrx_word' title='bbox 200 100 290 140'>due</span>
+        </span>"#;
+        
+        let occurrences = match_all_occurrences(hocr_sample, "amount due", 0.9, None);
+        assert_eq!(occurrences.len(), 2);
+        let boxes: Vec<BoundingBox> = occurrences.iter()
+            .map(|m| calculate_bounding_box_from_words(&m.word_boxes).unwrap())
+            .collect();
+        assert_eq!((boxes[0].x1, boxes[0].y1, boxes[0].x2), (100.0, 0.0, 290.0));
+        assert_eq!((boxes[1].x1, boxes[1].y1, boxes[1].x2), (100.0, 100.0, 290.0));
+        assert!(match_all_occurrences(hocr_sample, "amount due", 1.1, None).is_empty());
+    }
+
+    #[test]
+    fn test_find_box_incremental_matches_fresh_query() {
+        let words = ["Lorem", "ipsum", "the", "quick", "brown", "fox", "jumps", "over", "the", "lazy", "dog"];
+        let spans: String = words.iter()
+            .enumerate()
+            .map(|(i, w)| format!("<span class='ocrx_word' title='bbox {} 0 {} 40'>{}</span>", i * 100, i * 100 + 90, w))
+            .collect();
+        let hocr_sample = format!("<span class='ocr_line' title='bbox 0 0 1100 40'>{}</span>", spans);
+        let mut document = HocrDocument::new(&hocr_sample);
+        
+        let mut previous = String::new();
+        for query in ["quick", "quick brown", "quick brown fox", "quick brown fox jumps"] {
+            let incremental = document.find_box_incremental(&previous, query).unwrap();
+            let fresh = document.find_box(query).unwrap();
+            assert_eq!((incremental.x1, incremental.x2), (fresh.x1, fresh.x2), "{}", query);
+            assert_eq!(document.last_match, Some((query.to_string(), 3)));
+            previous = query.to_string();
+        }
+        
+        assert!(document.find_box_incremental(&previous, "quick brown fox jumps zzz qqq").is_some());
+        assert!(document.find_box_incremental("unrelated", "absent words here").is_none());
+        assert_eq!(document.last_match, None);
+        
+        // An exact match near the previous one must not hide an earlier exact match
+        let words = ["the", "quick", "a", "b", "c", "d", "the", "quic", "the", "quick"];
+        let spans: String = words.iter()
+            .enumerate()
+            .map(|(i, w)| format!("<span class='ocrx_word' title='bbox {} 0 {} 40'>{}</span>", i * 100, i * 100 + 90, w))
+            .collect();
+        let mut document = HocrDocument::new(&format!("<span class='ocr_line' title='bbox 0 0 1000 40'>{}</span>", spans));
+        
+        assert!(document.find_box_incremental("", "the quic").is_some());
+        assert_eq!(document.last_match, Some(("the quic".to_string(), 6)));
+        let incremental = document.find_box_incremental("the quic", "the quick").unwrap();
+        let fresh = document.find_box("the quick").unwrap();
+        assert_eq!((incremental.x1, incremental.x2), (fresh.x1, fresh.x2));
+        assert_eq!(document.last_match, Some(("the quick".to_string(), 0)));
+    }
+
+    #[test]
+    fn test_count_matches_by_line() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 600 40'>
+            <span class='ocrx_word' title='bbox 0 0 90 40'>Invoice</span>
+            <span class='ocrx_word' title='bbox 100 0 190 40'>number</span>
+            <span class='ocrx_word' title='bbox 200 0 290 40'>Date</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 100 600 140'>
+            <span class='ocrx_word' title='bbox 0 100 90 140'>Total</span>
+            <span class='ocrx_word' title='bbox 100 100 190 140'>due</span>
+        </span>"#;
+        
+        let terms: Vec<String> = ["invoice number", "date", "total due", "missing term"]
+            .iter().map(|s| s.to_string()).collect();
+        assert_eq!(count_matches_by_line(hocr_sample, &terms, 0.9), vec![(0, 2), (1, 1)]);
+    }
+
+    #[test]
+    fn test_ragged_line_boxes_for_mid_line_match() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 500 40'>
+            <span class='ocrx_word' title='bbox 0 0 90 40'>Before</span>
+            <span class='ocrx_word' title='bbox 100 0 190 40'>this</span>
+            <span class='ocrx_word' title='bbox 200 0 290 40'>match</span>
+            <span class='ocrx_word' title='bbox 300 0 500 40'>starts</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 50 480 90'>
+            <span class='ocrx_word' title='bbox 0 50 200 90'>somewhere</span>
+            <span class='ocrx_word' title='bbox 210 50 480 90'>inside</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 100 400 140'>
+            <span class='ocrx_word' title='bbox 0 100 120 140'>and</span>
+            <span class='ocrx_word' title='bbox 130 100 250 140'>ends</span>
+            <span class='ocrx_word' title='bbox 260 100 400 140'>later</span>
+        </span>"#;
+        
+        let word_match = match_word_boxes(hocr_sample, "match starts somewhere inside and ends").unwrap();
+        let boxes = ragged_line_boxes(&group_words_into_line_boxes(&word_match.word_boxes));
+        let edges: Vec<(f64, f64, f64)> = boxes.iter().map(|b| (b.x1, b.y1, b.x2)).collect();
+        assert_eq!(edges, vec![(200.0, 0.0, 500.0), (0.0, 50.0, 500.0), (0.0, 100.0, 250.0)]);
+        
+        let single = ragged_line_boxes(&[BoundingBox::new(200.0, 0.0, 290.0, 40.0)]);
+        assert_eq!((single[0].x1, single[0].x2), (200.0, 290.0));
+    }
+
+    #[test]
+    fn test_collect_matches_with_boxes() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 600 40'>
+            <span class='ocrx_word' title='bbox 0 0 90 40'>Invoice</span>
+            <span class='ocrx_word' title='bbox 100 0 190 40'>Number</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 100 600 140'>
+            <span class='ocrx_word' title='bbox 0 100 90 140'>Total</span>
+            <span class='ocrx_word' title='bbox 100 100 190 140'>Due</span>
+        </span>"#;
+        
+        let terms: Vec<String> = ["Invoice Number", "Total Due", "nothing like it"].iter().map(|s| s.to_string()).collect();
+        let matches = collect_matches_with_boxes(hocr_sample, &terms, 0.9);
+        assert_eq!(matches.len(), 2);
+        
+        let (query, result, bbox) = &matches[1];
+        assert_eq!(query, "Total Due");
+        assert_eq!(result.similarity, 1.0);
+        assert_eq!((result.start_index, result.end_index), (2, 4));
+        assert_eq!((bbox.x1, bbox.y1, bbox.x2, bbox.y2), (0.0, 100.0, 190.0, 140.0));
+    }
+
+    #[test]
+    fn test_split_pages_balances_nested_divs() {
+        let hocr_sample = concat!(
+            "<html><body>",
+            "<div class='ocr_page' id='page_1' title='bbox 0 0 100 100'>",
+            "<div class='ocr_carea'><p class='ocr_par'>one</p></div>",
+            "</div>\n",
+            "<div class=\"ocr_page\" id='page_2' title='bbox 0 0 100 100'>",
+            "<div class='ocr_carea'><div class='ocr_separator'/><p class='ocr_par'>two</p></div>",
+            "</div>",
+            "</body></html>",
+        );
+        
+        let pages = split_pages(hocr_sample);
+        assert_eq!(pages.len(), 2);
+        assert!(pages[0].starts_with("<div class='ocr_page' id='page_1'"));
+        assert!(pages[0].ends_with("</p></div></div>"));
+        assert!(pages[1].contains("two") && !pages[1].contains("one"));
+        assert!(pages[1].ends_with("</div></div>"));
+        assert_eq!(split_pages("<p>no pages</p>"), vec!["<p>no pages</p>"]);
+    }
+
+    #[test]
+    fn test_equal_matches_resolved_by_compactness() {
+        // Both lines read "net total"; the first pair is far apart, the second adjacent
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 2000 40'>
+            <span class='ocrx_word' title='bbox 0 0 90 40'>net</span>
+            <span class='ocrx_word' title='bbox 1900 0 2000 40'>total</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 100 300 140'>
+            <span class='ocrx_word' title='bbox 0 100 90 140'>net</span>
+            <span class='ocrx_word' title='bbox 100 100 200 140'>total</span>
+        </span>"#;
+        
+        let bbox = extract_bounding_box_from_hocr(hocr_sample, "net total").unwrap();
+        assert_eq!((bbox.x1, bbox.y1, bbox.x2, bbox.y2), (0.0, 100.0, 200.0, 140.0));
+    }
+
+    #[test]
+    fn test_exclude_superscripts_bridges_footnote_marker() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 700 40'>
+            <span class='ocrx_word' title='bbox 0 10 90 40'>the</span>
+            <span class='ocrx_word' title='bbox 100 10 190 40'>quick</span>
+            <span class='ocrx_word' title='bbox 200 10 290 40'>brown</span>
+            <span class='ocrx_word' title='bbox 292 0 302 15'>12</span>
+            <span class='ocrx_word' title='bbox 310 10 400 40'>fox</span>
+            <span class='ocrx_word' title='bbox 410 10 500 40'>jumps</span>
+            <span class='ocrx_word' title='bbox 505 10 520 40'><sup>3</sup></span>
+            <span class='ocrx_word' title='bbox 530 10 600 40'>over</span>
+        </span>"#;
+        
+        let words = extract_word_bounding_boxes(hocr_sample);
+        let flagged: Vec<&str> = words.iter().filter(|w| w.superscript).map(|w| w.text.as_str()).collect();
+        assert_eq!(flagged, vec!["12", "3"]);
+        
+        let plain = find_hocr_match(hocr_sample, "brown fox jumps over", &ExtractOptions::default()).unwrap();
+        assert!(plain.similarity < 1.0);
+        
+        let mut options = ExtractOptions::new();
+        options.exclude_superscripts = true;
+        let result = find_hocr_match(hocr_sample, "brown fox jumps over", &options).unwrap();
+        assert_eq!(result.similarity, 1.0);
+        assert_eq!((result.bbox.x1, result.bbox.x2), (200.0, 600.0));
+    }
+
+    #[test]
+    fn test_fractional_bbox_coordinates() {
+        let hocr_sample = r#"
+        <div class='ocr_page' title='bbox 0 0 2550.5 3300'>
+        <p class='ocr_par'>
+        <span class='ocr_line' title='bbox 100.5 200.2 300.9 400.1'>
+            <span class='ocrx_word' title='bbox 100.5 200.2 190.25 400.1'>Hello</span>
+            <span class='ocrx_word' title='bbox 200.75 200.2 300.9 400.1'>World</span>
+        </span>
+        </p>
+        </div>"#;
+        
+        let words = extract_word_bounding_boxes(hocr_sample);
+        assert_eq!(words.len(), 2);
+        assert_eq!((words[0].x1, words[0].y1, words[0].x2, words[0].y2), (100.5, 200.2, 190.25, 400.1));
+        assert_eq!(extract_page_dimensions(hocr_sample).unwrap().width, 2550.5);
+        
+        let embedded_text = extract_embedded_text_from_hocr(hocr_sample);
+        assert!(embedded_text.contains("[[LINE 100.5 200.2 300.9 400.1]]"));
+        let legacy = extract_bounding_box(&embedded_text, "Hello World").unwrap();
+        assert_eq!((legacy.x1, legacy.y1), (100.5, 200.2));
+    }
+
+    #[test]
+    fn test_word_color_from_title_or_style() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 500 40'>
+            <span class='ocrx_word' title='bbox 0 0 90 40'>plain</span>
+            <span class='ocrx_word' title='bbox 100 0 190 40; fcolor #ff0000'>red</span>
+            <span class='ocrx_word' style='font-weight: bold; color: #00f' title='bbox 200 0 290 40'>blue</span>
+            <span class='ocrx_word' style='background-color: yellow' title='bbox 300 0 390 40'>marked</span>
+        </span>"#;
+        
+        let words = extract_word_bounding_boxes(hocr_sample);
+        let colors: Vec<Option<&str>> = words.iter().map(|w| w.color.as_deref()).collect();
+        assert_eq!(colors, vec![None, Some("#ff0000"), Some("#00f"), None]);
+    }
+
+    #[test]
+    fn test_word_box_for_index_range_from_match_result() {
+        let hocr_sample = r#"
+        <p class='ocr_par'>
+        <span class='ocr_line' title='bbox 0 0 500 40'>
+            <span class='ocrx_word' title='bbox 0 0 90 40'>The</span>
+            <span class='ocrx_word' title='bbox 100 0 190 40'>quick</span>
+            <span class='ocrx_word' title='bbox 200 0 290 40'>brown</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 50 500 90'>
+            <span class='ocrx_word' title='bbox 0 50 90 90'>fox</span>
+            <span class='ocrx_word' title='bbox 100 50 190 90'>jumps</span>
+        </span>
+        </p>"#;
+        
+        let embedded_text = extract_embedded_text_from_hocr(hocr_sample);
+        let result = find_closest_match(&embedded_text, "brown fox").unwrap();
+        assert_eq!((result.start_index, result.end_index), (2, 4));
+        
+        let bbox = word_box_for_index_range(hocr_sample, result.start_index, result.end_index).unwrap();
+        assert_eq!((bbox.x1, bbox.y1, bbox.x2, bbox.y2), (0.0, 0.0, 290.0, 90.0));
+        assert!(word_box_for_index_range(hocr_sample, 3, 3).is_none());
+        assert!(word_box_for_index_range(hocr_sample, 4, 6).is_none());
+    }
+
+    #[test]
+    fn test_dominant_word_height_ignores_headings() {
+        let mut hocr_sample = String::from("<span class='ocr_line' title='bbox 0 0 2000 120'>");
+        for (i, height) in [120, 118, 20, 21, 20, 20, 19, 21, 20].iter().enumerate() {
+            hocr_sample.push_str(&format!(
+                "<span class='ocrx_word' title='bbox {} 0 {} {}'>w{}</span>", i * 100, i * 100 + 90, height, i
+            ));
+        }
+        hocr_sample.push_str("</span>");
+        
+        let height = dominant_word_height(&hocr_sample).unwrap();
+        assert!((height - 20.0).abs() < 1.0, "{}", height);
+        assert!(average_matched_word_height(&hocr_sample, "w0 w1 w2 w3 w4 w5 w6 w7 w8").unwrap() > 40.0);
+        assert_eq!(dominant_word_height("<p>no words</p>"), None);
+    }
+
+    #[test]
+    fn test_normalized_match_box_divides_by_page_size() {
+        let hocr_sample = r#"
+        <div class='ocr_page' title='image "scan.png"; bbox 0 0 1000 800'>
+        <span class='ocr_line' title='bbox 100 200 600 240'>
+            <span class='ocrx_word' title='bbox 100 200 300 240'>Invoice</span>
+            <span class='ocrx_word' title='bbox 350 200 500 240'>total</span>
+        </span>
+        </div>"#;
+        
+        let bbox = normalized_match_box(hocr_sample, "Invoice total").unwrap();
+        assert_eq!((bbox.x1, bbox.y1, bbox.x2, bbox.y2), (0.1, 0.25, 0.5, 0.3));
+        
+        let without_page = hocr_sample.replace("ocr_page", "ocr_area");
+        assert!(normalized_match_box(&without_page, "Invoice total").is_none());
+    }
+
+    #[test]
+    fn test_two_column_reading_order() {
+        // Each line runs across both columns, so DOM order interleaves them
+        let hocr_sample = r#"
+        <div class='ocr_page' title='bbox 0 0 1000 400'>
+        <span class='ocr_line' title='bbox 50 100 950 130'>
+            <span class='ocrx_word' title='bbox 50 100 200 130'>payment</span>
+            <span class='ocrx_word' title='bbox 550 100 700 130'>shipping</span>
+        </span>
+        <span class='ocr_line' title='bbox 50 140 950 170'>
+            <span class='ocrx_word' title='bbox 50 140 200 170'>received</span>
+            <span class='ocrx_word' title='bbox 550 140 700 170'>address</span>
+        </span>
+        <span class='ocr_line' title='bbox 50 180 950 210'>
+            <span class='ocrx_word' title='bbox 50 180 200 210'>in</span>
+            <span class='ocrx_word' title='bbox 550 180 700 210'>below</span>
+        </span>
+        <span class='ocr_line' title='bbox 50 220 950 250'>
+            <span class='ocrx_word' title='bbox 50 220 200 250'>full</span>
+            <span class='ocrx_word' title='bbox 550 220 700 250'>please</span>
+        </span>
+        </div>"#;
+        
+        let interleaved = find_hocr_match(hocr_sample, "payment received in full", &ExtractOptions::default());
+        assert!(interleaved.is_none_or(|m| m.similarity < 1.0));
+        
+        let mut options = ExtractOptions::new();
+        options.two_column = true;
+        let result = find_hocr_match(hocr_sample, "payment received in full", &options).unwrap();
+        assert_eq!(result.similarity, 1.0);
+        assert_eq!(result.matched_text, "payment received in full");
+        assert_eq!((result.bbox.x1, result.bbox.y1, result.bbox.x2, result.bbox.y2), (50.0, 100.0, 200.0, 250.0));
+    }
+
+    #[test]
+    fn test_match_gaps_flags_scattered_match() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 100 100 400 130'>
+            <span class='ocrx_word' title='bbox 100 100 180 130'>net</span>
+            <span class='ocrx_word' title='bbox 190 100 280 130'>amount</span>
+            <span class='ocrx_word' title='bbox 290 100 360 130'>due</span>
+        </span>
+        <span class='ocr_line' title='bbox 100 900 400 930'>
+            <span class='ocrx_word' title='bbox 100 900 180 930'>see</span>
+            <span class='ocrx_word' title='bbox 190 900 280 930'>terms</span>
+        </span>"#;
+        
+        let tight = match_gaps(hocr_sample, "net amount due").unwrap();
+        assert_eq!(tight.gaps, vec![(10.0, 0.0), (10.0, 0.0)]);
+        assert!(!tight.suspicious);
+        
+        let scattered = match_gaps(hocr_sample, "amount due see").unwrap();
+        assert_eq!(scattered.gaps, vec![(10.0, 0.0), (0.0, 770.0)]);
+        assert!(scattered.suspicious);
+    }
+
+    #[test]
+    fn test_clean_word_folds_sharp_s_and_turkish_i() {
+        assert_eq!(clean_word("STRASSE"), clean_word("Straße"));
+        assert_eq!(clean_word("GROẞ"), "gross");
+        assert_eq!(clean_word("İstanbul"), "istanbul");
+        assert_eq!(clean_word("ıstanbul"), clean_word("ISTANBUL"));
+        
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 100 100 500 130'>
+            <span class='ocrx_word' title='bbox 100 100 250 130'>STRASSE</span>
+            <span class='ocrx_word' title='bbox 260 100 400 130'>İSTANBUL</span>
+        </span>"#;
+        let result = find_hocr_match(hocr_sample, "Straße istanbul", &ExtractOptions::default()).unwrap();
+        assert_eq!(result.similarity, 1.0);
+    }
+
+    #[test]
+    fn test_single_line_match_rejects_line_spanning_match() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 100 100 500 130'>
+            <span class='ocrx_word' title='bbox 100 100 200 130'>invoice</span>
+            <span class='ocrx_word' title='bbox 210 100 300 130'>total</span>
+            <span class='ocrx_word' title='bbox 310 100 420 130'>amount</span>
+        </span>
+        <span class='ocr_line' title='bbox 100 140 500 170'>
+            <span class='ocrx_word' title='bbox 100 140 160 170'>due</span>
+            <span class='ocrx_word' title='bbox 170 140 260 170'>today</span>
+        </span>
+        <span class='ocr_line' title='bbox 100 300 500 330'>
+            <span class='ocrx_word' title='bbox 100 300 190 330'>totai</span>
+            <span class='ocrx_word' title='bbox 200 300 310 330'>amount</span>
+            <span class='ocrx_word' title='bbox 320 300 380 330'>due</span>
+        </span>"#;
+        
+        let overall = extract_bounding_box_from_hocr(hocr_sample, "total amount due").unwrap();
+        assert_eq!((overall.y1, overall.y2), (100.0, 170.0));
+        
+        let result = best_single_line_match(hocr_sample, "total amount due").unwrap();
+        assert!(result.similarity < 1.0);
+        assert_eq!((result.bbox.x1, result.bbox.y1, result.bbox.x2, result.bbox.y2), (100.0, 300.0, 380.0, 330.0));
+        
+        assert!(best_single_line_match(hocr_sample, "nothing like this").is_none());
+    }
+
+    #[test]
+    fn test_line_leaders_first_two_words() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 100 100 500 130'>
+            <span class='ocrx_word' title='bbox 100 100 180 130'>Chapter</span>
+            <span class='ocrx_word' title='bbox 190 100 220 130'>1</span>
+            <span class='ocrx_word' title='bbox 230 100 400 130'>Beginnings</span>
+        </span>
+        <span class='ocr_line' title='bbox 100 140 500 170'>
+            <span class='ocrx_word' title='bbox 100 140 180 170'>Chapter</span>
+            <span class='ocrx_word' title='bbox 190 140 220 170'>2</span>
+            <span class='ocrx_word' title='bbox 230 140 350 170'>Endings</span>
+        </span>"#;
+        
+        let leaders = line_leaders(hocr_sample, 2);
+        assert_eq!(leaders.len(), 2);
+        assert_eq!((leaders[0].0, leaders[0].1.as_str()), (0, "Chapter 1"));
+        assert_eq!((leaders[1].0, leaders[1].1.as_str()), (1, "Chapter 2"));
+        let bbox = &leaders[1].2;
+        assert_eq!((bbox.x1, bbox.y1, bbox.x2, bbox.y2), (100.0, 140.0, 220.0, 170.0));
+    }
+
+    #[test]
+    fn test_idf_weighting_prefers_rare_word() {
+        let words = "the lion of the park and the dog of the park saw a zebra at one gate";
+        let spans: String = words.split(' ').enumerate()
+            .map(|(i, word)| format!(
+                "<span class='ocrx_word' title='bbox {} 100 {} 130'>{}</span>",
+                i * 100, i * 100 + 90, word
+            ))
+            .collect();
+        let hocr_sample = format!("<span class='ocr_line' title='bbox 0 100 1700 130'>{}</span>", spans);
+        
+        let plain = find_hocr_match(&hocr_sample, "the zebra of the gate", &ExtractOptions::default()).unwrap();
+        assert_eq!(plain.matched_text, "the lion of the park");
+        
+        let weighted = idf_weighted_match(&hocr_sample, "the zebra of the gate").unwrap();
+        assert_eq!(weighted.matched_text, "a zebra at one gate");
+        assert!(weighted.similarity > 0.5);
+    }
+
+    #[test]
+    fn test_merge_full_width_lines_joins_interior_lines() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 500 40'>
+            <span class='ocrx_word' title='bbox 0 0 190 40'>Terms</span>
+            <span class='ocrx_word' title='bbox 200 0 500 40'>apply</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 50 480 90'>
+            <span class='ocrx_word' title='bbox 0 50 200 90'>to</span>
+            <span class='ocrx_word' title='bbox 210 50 480 90'>every</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 100 450 140'>
+            <span class='ocrx_word' title='bbox 0 100 200 140'>order</span>
+            <span class='ocrx_word' title='bbox 210 100 450 140'>placed</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 150 400 190'>
+            <span class='ocrx_word' title='bbox 0 150 120 190'>online</span>
+            <span class='ocrx_word' title='bbox 130 150 400 190'>today</span>
+        </span>"#;
+        
+        let word_match = match_word_boxes(hocr_sample, "apply to every order placed online").unwrap();
+        let line_boxes = ragged_line_boxes(&group_words_into_line_boxes(&word_match.word_boxes));
+        assert_eq!(line_boxes.len(), 4);
+        
+        let merged = merge_full_width_lines(&line_boxes);
+        let edges: Vec<(f64, f64, f64, f64)> = merged.iter().map(|b| (b.x1, b.y1, b.x2, b.y2)).collect();
+        assert_eq!(edges, vec![
+            (200.0, 0.0, 500.0, 40.0),
+            (0.0, 50.0, 500.0, 140.0),
+            (0.0, 150.0, 120.0, 190.0),
+        ]);
+    }
+
+    #[test]
+    fn test_whitespace_only_search_is_handled() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 500 40'>
+            <span class='ocrx_word' title='bbox 0 0 90 40'>alpha</span>
+        </span>"#;
+        let embedded = extract_embedded_text_from_hocr(hocr_sample);
+        
+        assert!(find_closest_match(&embedded, "   ").is_none());
+        assert!(extract_bounding_box(&embedded, "   ").is_none());
+        assert!(extract_bounding_box_from_hocr(hocr_sample, "   ").is_none());
+        assert_eq!(
+            diagnose_match(hocr_sample, "   ").as_deref(),
+            Some("BlankSearch: search string contains only whitespace"),
+        );
+        assert_eq!(diagnose_match(hocr_sample, "alpha"), None);
+    }
+
+    #[test]
+    fn test_match_hocr_byte_span_covers_matched_elements() {
+        let hocr_sample = r#"<div class='ocr_page'>
+        <span class='ocr_line' title='bbox 0 0 500 40'>
+            <span class='ocrx_word' title='bbox 0 0 90 40'>Café</span>
+            <span class='ocrx_word' title='bbox 100 0 200 40'><b>opening</b></span>
+            <span class='ocrx_word' title='bbox 210 0 300 40'>hours</span>
+            <span class='ocrx_word' title='bbox 310 0 400 40'>daily</span>
+        </span></div>"#;
+        
+        let (start, end) = match_hocr_byte_span(hocr_sample, "opening hours").unwrap();
+        let slice = &hocr_sample[start..end];
+        assert!(slice.starts_with("<span class='ocrx_word' title='bbox 100 0 200 40'>"));
+        assert!(slice.ends_with("hours</span>"));
+        assert!(!slice.contains("Café") && !slice.contains("daily"));
+    }
+
+    #[test]
+    fn test_word_height_buckets_two_sizes() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 500 60'>
+            <span class='ocrx_word' title='bbox 0 0 200 52'>Annual</span>
+            <span class='ocrx_word' title='bbox 210 2 400 57'>Report</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 100 500 120'>
+            <span class='ocrx_word' title='bbox 0 100 90 112'>revenue</span>
+            <span class='ocrx_word' title='bbox 100 100 190 114'>grew</span>
+            <span class='ocrx_word' title='bbox 200 101 290 116'>again</span>
+        </span>"#;
+        
+        assert_eq!(word_height_buckets(hocr_sample, 10.0), vec![(10.0, 3), (50.0, 2)]);
+        assert!(word_height_buckets(hocr_sample, 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_inverted_word_box_is_repaired() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 500 40'>
+            <span class='ocrx_word' title='bbox 0 0 90 40'>Signed</span>
+            <span class='ocrx_word' title='bbox 200 40 100 0'>copy</span>
+        </span>"#;
+        
+        let words = extract_word_bounding_boxes(hocr_sample);
+        assert_eq!(words.len(), 2);
+        assert_eq!((words[1].x1, words[1].y1, words[1].x2, words[1].y2), (100.0, 0.0, 200.0, 40.0));
+        
+        let bbox = extract_bounding_box_from_hocr(hocr_sample, "signed copy").unwrap();
+        assert_eq!((bbox.x1, bbox.y1, bbox.x2, bbox.y2), (0.0, 0.0, 200.0, 40.0));
+    }
+
+    #[test]
+    fn test_match_centroid_weights_by_area() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 500 40'>
+            <span class='ocrx_word' title='bbox 0 0 300 40'>Balance</span>
+            <span class='ocrx_word' title='bbox 400 0 500 40'>due</span>
+        </span>"#;
+        
+        // Areas 12000 and 4000 with centers x = 150 and 450
+        assert_eq!(match_centroid_point(hocr_sample, "balance due"), Some((225.0, 20.0)));
+        assert_eq!(match_centroid_point(hocr_sample, "due"), Some((450.0, 20.0)));
+    }
+
+    #[test]
+    fn test_wildcard_matches_capture_following_word() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 600 40'>
+            <span class='ocrx_word' title='bbox 0 0 80 40'>Invoice</span>
+            <span class='ocrx_word' title='bbox 90 0 140 40'>No.</span>
+            <span class='ocrx_word' title='bbox 150 0 300 40'>A-1042</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 50 600 90'>
+            <span class='ocrx_word' title='bbox 0 50 50 90'>no.</span>
+            <span class='ocrx_word' title='bbox 60 50 200 90'>7731</span>
+        </span>"#;
+        
+        let found = wildcard_matches(hocr_sample, "No. *");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].captures, vec!["A-1042"]);
+        assert_eq!(found[0].text, "No. A-1042");
+        assert_eq!((found[0].bbox.x1, found[0].bbox.x2), (90.0, 300.0));
+        assert_eq!(found[1].captures, vec!["7731"]);
+        
+        assert!(wildcard_matches(hocr_sample, "Date: *").is_empty());
+    }
+
+    #[test]
+    fn test_legacy_box_stable_across_paragraph_marker_spacing() {
+        let compact = "[[PARAGRAPH]] [[LINE 10 10 200 30]] intro words here [[PARAGRAPH]] \
+            [[LINE 10 50 300 70]] total due now [[PARAGRAPH]] [[LINE 10 90 300 110]] thanks";
+        let spaced = "[[PARAGRAPH]]\n\n  [[LINE 10 10 200 30]]\nintro  words here\n[[PARAGRAPH]]\n\n\
+            [[LINE 10 50 300 70]]\n  total due now\n[[PARAGRAPH]]\n[[LINE 10 90 300 110]] thanks";
+        
+        let expected = extract_bounding_box(compact, "total due now").unwrap();
+        let bbox = extract_bounding_box(spaced, "total due now").unwrap();
+        assert_eq!((expected.x1, expected.y1), (10.0, 50.0));
+        assert_eq!((bbox.x1, bbox.y1, bbox.x2, bbox.y2), (expected.x1, expected.y1, expected.x2, expected.y2));
+    }
+
+    #[test]
+    fn test_unquoted_title_attribute() {
+        let hocr_sample = "
+        <span class=ocr_line title=bbox 0 0 500 40; baseline 0 -5>
+            <span class=ocrx_word title=bbox 10 5 120 38; x_wconf 91 id=word_1_1>Unquoted</span>
+            <span title=bbox 130 5 220 38 class='ocrx_word'>title</span>
+        </span>";
+        
+        let attributes = parse_tag_attributes("class=ocrx_word title=bbox 10 5 120 38; x_wconf 91 id=w1 /");
+        assert_eq!(attributes.get("title").map(String::as_str), Some("bbox 10 5 120 38; x_wconf 91"));
+        assert_eq!(attributes.get("id").map(String::as_str), Some("w1"));
+        
+        let words = extract_word_bounding_boxes(hocr_sample);
+        assert_eq!(words.len(), 2);
+        assert_eq!((words[0].x1, words[0].y1, words[0].x2, words[0].y2), (10.0, 5.0, 120.0, 38.0));
+        assert_eq!((words[1].x1, words[1].x2), (130.0, 220.0));
+        assert_eq!(words[0].line_index, 0);
+    }
+
+    #[test]
+    fn test_column_ranges_two_columns() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 50 100 950 130'>
+            <span class='ocrx_word' title='bbox 50 100 160 130'>left</span>
+            <span class='ocrx_word' title='bbox 175 100 400 130'>column</span>
+            <span class='ocrx_word' title='bbox 560 100 700 130'>right</span>
+            <span class='ocrx_word' title='bbox 715 100 900 130'>column</span>
+        </span>
+        <span class='ocr_line' title='bbox 50 140 950 170'>
+            <span class='ocrx_word' title='bbox 50 140 250 170'>continues</span>
+            <span class='ocrx_word' title='bbox 265 140 420 170'>here</span>
+            <span class='ocrx_word' title='bbox 560 140 760 170'>continues</span>
+            <span class='ocrx_word' title='bbox 775 140 930 170'>too</span>
+        </span>"#;
+        
+        assert_eq!(column_ranges(hocr_sample), vec![(50.0, 420.0), (560.0, 930.0)]);
+        assert!(column_ranges("<p>no words</p>").is_empty());
+    }
+
+    #[test]
+    fn test_extract_nth_match_box_picks_second_occurrence() {
+        let hocr_sample: String = (0..3)
+            .map(|i| format!(
+                "<span class='ocr_line' title='bbox 0 {top} 600 {bottom}'>\
+                 <span class='ocrx_word' title='bbox 0 {top} 200 {bottom}'>Account</span>\
+                 <span class='ocrx_word' title='bbox 210 {top} 400 {bottom}'>summary</span></span>",
+                top = i * 1000,
+                bottom = i * 1000 + 40,
+            ))
+            .collect();
+        
+        let second = extract_nth_match_box(&hocr_sample, "account summary", 1, 0.9).unwrap();
+        assert_eq!((second.x1, second.y1, second.x2, second.y2), (0.0, 1000.0, 400.0, 1040.0));
+        assert!(extract_nth_match_box(&hocr_sample, "account summary", 3, 0.9).is_none());
+    }
+
+    #[test]
+    fn test_word_count_in_region_uses_word_centers() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 400 40'>
+            <span class='ocrx_word' title='bbox 0 0 80 40'>Total</span>
+            <span class='ocrx_word' title='bbox 100 0 180 40'>amount</span>
+            <span class='ocrx_word' title='bbox 200 0 280 40'>due</span>
+            <span class='ocrx_word' title='bbox 300 0 380 40'>today</span>
+        </span>"#;
+        
+        // Overlaps "Total" and "today" only partly, so neither center is inside
+        assert_eq!(word_count_in_region(hocr_sample, 50.0, 0.0, 330.0, 40.0), 2);
+        assert_eq!(word_count_in_region(hocr_sample, 0.0, 100.0, 400.0, 200.0), 0);
+    }
+
+    #[test]
+    fn test_max_span_width_rejects_scattered_match() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 2000 40'>
+            <span class='ocrx_word' title='bbox 0 0 80 40'>Amount</span>
+            <span class='ocrx_word' title='bbox 1800 0 1900 40'>paid</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 50 400 90'>
+            <span class='ocrx_word' title='bbox 0 50 80 90'>Total</span>
+            <span class='ocrx_word' title='bbox 100 50 160 90'>due</span>
+        </span>"#;
+        
+        let mut options = ExtractOptions::new();
+        options.max_span_width = Some(600.0);
+        assert!(extract_bounding_box_from_hocr(hocr_sample, "amount paid").is_some());
+        assert!(extract_bounding_box_from_hocr_with_options(hocr_sample, "amount paid", &options).is_none());
+        
+        let bbox = extract_bounding_box_from_hocr_with_options(hocr_sample, "total due", &options).unwrap();
+        assert_eq!((bbox.x1, bbox.x2), (0.0, 160.0));
+    }
+
+    #[test]
+    fn test_candidate_matches_best_first_without_overlap() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 400 40'>
+            <span class='ocrx_word' title='bbox 0 0 80 40'>Amount</span>
+            <span class='ocrx_word' title='bbox 100 0 160 40'>owed</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 50 400 90'>
+            <span class='ocrx_word' title='bbox 0 50 80 90'>Amount</span>
+            <span class='ocrx_word' title='bbox 100 50 160 90'>due</span>
+        </span>"#;
+        
+        let candidates = candidate_matches(hocr_sample, "amount due", 2);
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].matched_text, "Amount due");
+        assert_eq!(candidates[0].bbox.y1, 50.0);
+        assert_eq!(candidates[1].matched_text, "Amount owed");
+        assert!(candidates[1].similarity < candidates[0].similarity);
+        
+        assert_eq!(candidate_matches(hocr_sample, "amount due", 1).len(), 1);
+    }
+
+    #[test]
+    fn test_estimate_line_skew_fits_word_centers() {
+        // Centers at (50, 20), (150, 30), (250, 40): a slope of 0.1
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 0 0 300 60'>
+            <span class='ocrx_word' title='bbox 0 0 100 40'>Slightly</span>
+            <span class='ocrx_word' title='bbox 100 10 200 50'>tilted</span>
+            <span class='ocrx_word' title='bbox 200 20 300 60'>scan</span>
+        </span>
+        <span class='ocr_line' title='bbox 0 100 300 140'>
+            <span class='ocrx_word' title='bbox 0 100 100 140'>Level</span>
+            <span class='ocrx_word' title='bbox 150 100 300 140'>line</span>
+        </span>"#;
+        
+        let skew = estimate_line_skew(hocr_sample, 0).unwrap();
+        assert!((skew - 0.1f64.atan().to_degrees()).abs() < 1e-9);
+        assert_eq!(estimate_line_skew(hocr_sample, 1), Some(0.0));
+        assert_eq!(estimate_line_skew(hocr_sample, 2), None);
+    }
+
+    #[test]
+    fn test_match_terms_across_pages_sorted_by_page_then_position() {
+        let page = |words: &[&str]| {
+            let spans: String = words.iter()
+                .enumerate()
+                .map(|(i, w)| format!("<span class='ocrx_word' title='bbox {} 10 {} 40'>{}</span>", i * 100, i * 100 + 90, w))
+                .collect();
+            format!("<div class='ocr_page' title='bbox 0 0 1000 1000'><span class='ocr_line' title='bbox 0 10 900 40'>{}</span></div>", spans)
+        };
+        let hocr_sample = [
+            page(&["Invoice", "number", "Acme", "Corp", "total"]),
+            page(&["Acme", "Corp", "payment", "terms", "apply"]),
+        ].concat();
+        let terms = vec!["Acme Corp".to_string(), "Invoice number".to_string(), "payment terms".to_string()];
+        
+        let matches = match_terms_across_pages(&hocr_sample, &terms, 0.9);
+        let order: Vec<(usize, &str)> = matches.iter().map(|m| (m.page, m.term.as_str())).collect();
+        assert_eq!(order, vec![
+            (0, "Invoice number"),
+            (0, "Acme Corp"),
+            (1, "Acme Corp"),
+            (1, "payment terms"),
+        ]);
+        assert_eq!(matches[1].bbox.x1, 200.0);
+        assert_eq!(matches[3].start_index, 2);
+    }
+
+    #[test]
+    fn test_extract_bounding_box_none_when_no_window_scores() {
+        assert!(extract_bounding_box("[[PARAGRAPH]] hi there", "a b c d e f").is_none());
+        assert!(extract_bounding_box("[[PARAGRAPH]] [[LINE 1 2 3 4]] abc def", "zzz").is_none());
+    }
+
+    #[test]
+    fn test_match_all_occurrences_max_results() {
+        let words = ["the", "cat", "saw", "the", "dog", "and", "the", "bird", "near", "the", "tree", "by", "the", "lake"];
+        let spans: String = words.iter()
+            .enumerate()
+            .map(|(i, w)| format!("<span class='ocrx_word' title='bbox {} 0 {} 40'>{}</span>", i * 100, i * 100 + 90, w))
+            .collect();
+        let hocr_sample = format!("<span class='ocr_line' title='bbox 0 0 1400 40'>{}</span>", spans);
+        
+        assert_eq!(match_all_occurrences(&hocr_sample, "the", 1.0, None).len(), 5);
+        let capped = match_all_occurrences(&hocr_sample, "the", 1.0, Some(2));
+        let starts: Vec<usize> = capped.iter().map(|m| m.first_box).collect();
+        assert_eq!(starts, vec![0, 3]);
+    }
+
+    #[test]
+    fn test_has_opening_tag_requires_tag_boundary() {
+        assert!(has_opening_tag("12<sup>3</sup>", "sup"));
+        assert!(has_opening_tag("12<sup class=\"fn\">3</sup>", "sup"));
+        assert!(!has_opening_tag("12<super>3</super>", "sup"));
+        assert!(!has_opening_tag("123", "sup"));
+    }
+
+    #[test]
+    fn test_word_box_for_index_range_with_unboxed_and_non_ascii_words() {
+        let hocr_sample = r#"
+        <p class='ocr_par'>
+        <span class='ocr_line' title='bbox 0 0 500 40'>
+            <span class='ocrx_word' title='x_wconf 90'>Die</span>
+            <span class='ocrx_word' title='bbox 100 0 190 40'>Straße</span>
+            <span class='ocrx_word' title='bbox 200 0 290 40'>İstanbul</span>
+            <span class='ocrx_word' title='bbox 300 0 390 40'>Nord</span>
+        </span>
+        </p>
+        <span class='ocrx_word' title='bbox 0 900 90 940'>Footer</span>"#;
+        
+        let embedded_text = extract_embedded_text_from_hocr(hocr_sample);
+        let result = find_closest_match(&embedded_text, "İstanbul Nord").unwrap();
+        assert_eq!((result.start_index, result.end_index), (2, 4));
+        
+        let bbox = word_box_for_index_range(hocr_sample, result.start_index, result.end_index).unwrap();
+        assert_eq!((bbox.x1, bbox.y1, bbox.x2, bbox.y2), (200.0, 0.0, 390.0, 40.0));
+        let bbox = word_box_for_index_range(hocr_sample, 0, 2).unwrap();
+        assert_eq!((bbox.x1, bbox.x2), (100.0, 190.0));
+        assert!(word_box_for_index_range(hocr_sample, 0, 1).is_none());
+        assert!(word_box_for_index_range(hocr_sample, 4, 5).is_none());
     }
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
 mod utils;
+mod errors;
 mod string_matching;
 mod hocr_parser;
 mod pdf_annotator;
@@ -79,6 +80,7 @@ pub fn get_version_info() -> js_sys::Object {
 }
 
 // Export the main string matching functionality
+pub use errors::*;
 pub use string_matching::*;
 pub use hocr_parser::*;
 pub use pdf_annotator::*;
\ No newline at end of file
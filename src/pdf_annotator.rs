@@ -1,8 +1,10 @@
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
+use crate::hocr_parser::{find_hocr_match, matched_word_regions, median_line_spacing, ExtractOptions};
 
 #[wasm_bindgen]
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct AnnotationStyle {
     pub border_color_r: f64,
     pub border_color_g: f64, 
@@ -18,6 +20,12 @@ pub struct AnnotationStyle {
     pub font_color_b: f64,
 }
 
+impl Default for AnnotationStyle {
+    fn default() -> Self {
+        AnnotationStyle::new()
+    }
+}
+
 #[wasm_bindgen]
 impl AnnotationStyle {
     #[wasm_bindgen(constructor)]
@@ -96,8 +104,29 @@ impl AnnotationStyle {
             font_color_b: 0.5,
         }
     }
+    
+    #[wasm_bindgen]
+    pub fn note_style() -> AnnotationStyle {
+        AnnotationStyle {
+            border_color_r: 0.8,
+            border_color_g: 0.6,
+            border_color_b: 0.0,
+            fill_color_r: 1.0,
+            fill_color_g: 0.9,
+            fill_color_b: 0.3,
+            opacity: 1.0,
+            border_width: 1.0,
+            font_size: 10.0,
+            font_color_r: 0.0,
+            font_color_g: 0.0,
+            font_color_b: 0.0,
+        }
+    }
 }
 
+/// Side length in PDF points of the icon placed by `create_note_annotation`
+pub const NOTE_ICON_SIZE: f64 = 18.0;
+
 #[wasm_bindgen]
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct CoordinateTransform {
@@ -146,6 +175,8 @@ pub struct AnnotationData {
     pub style: AnnotationStyle,
     pub similarity_score: f64,
     pub matched_text: String,
+    /// Drawing order; lower values are drawn first (underneath)
+    pub z_index: i32,
 }
 
 impl AnnotationData {
@@ -162,10 +193,16 @@ impl AnnotationData {
             style,
             similarity_score,
             matched_text,
+            z_index: 0,
         }
     }
 }
 
+/// Put annotations in drawing order: ascending `z_index`, input order among equals
+pub fn sort_annotations_by_z_index(annotations: &mut [AnnotationData]) {
+    annotations.sort_by_key(|annotation| annotation.z_index);
+}
+
 /// Calculate coordinate transformation from hOCR to PDF space
 /// This follows the enhanced logic from the TypeScript implementation
 #[wasm_bindgen]
@@ -181,6 +218,15 @@ pub fn calculate_coordinate_transform(
     CoordinateTransform::new(scale_x, scale_y, 0.0, 0.0, pdf_page_height)
 }
 
+/// Build a transform for hOCR pixels scanned at `scan_dpi` onto a PDF page in points
+/// PDF user space is 72 points per inch, so both axes scale by `72 / scan_dpi`
+#[wasm_bindgen]
+pub fn coordinate_transform_from_dpi(scan_dpi: f64, pdf_page_height_points: f64) -> CoordinateTransform {
+    let scale = 72.0 / scan_dpi;
+    
+    CoordinateTransform::new(scale, scale, 0.0, 0.0, pdf_page_height_points)
+}
+
 /// Transform hOCR coordinates to PDF coordinates - EXACT JavaScript algorithm port
 /// Based on the JavaScript PDFAnnotator.transformCoordinates method
 #[wasm_bindgen]
@@ -207,12 +253,57 @@ pub fn transform_coordinates(
     PDFCoordinates::new(x, y, width, height)
 }
 
+/// Line pitch in PDF points: the median spacing of `ocr_line` tops times `scale_y`
+/// None when the hOCR has fewer than two lines
+#[wasm_bindgen]
+pub fn pdf_line_height(hocr_content: &str, transform: &CoordinateTransform) -> Option<f64> {
+    median_line_spacing(hocr_content).map(|spacing| spacing * transform.scale_y)
+}
+
+/// Where the corners of an hOCR page land in PDF space, mapped like `transform_coordinates`
+/// Order: top-left, top-right, bottom-right, bottom-left (as seen on the hOCR page)
+pub fn page_corner_points(
+    hocr_page_width: f64,
+    hocr_page_height: f64,
+    transform: &CoordinateTransform,
+) -> [(f64, f64); 4] {
+    [
+        (0.0, 0.0),
+        (hocr_page_width, 0.0),
+        (hocr_page_width, hocr_page_height),
+        (0.0, hocr_page_height),
+    ]
+    .map(|(x, y)| {
+        let point = transform_coordinates(x, y, x, y, transform);
+        (point.x, point.y)
+    })
+}
+
+/// Transformed page corners as `{x, y}` objects, for sanity-checking the y-flip
+/// Order: top-left, top-right, bottom-right, bottom-left (as seen on the hOCR page)
+#[wasm_bindgen]
+pub fn transform_page_corners(
+    hocr_page_width: f64,
+    hocr_page_height: f64,
+    transform: &CoordinateTransform,
+) -> js_sys::Array {
+    let corners = js_sys::Array::new();
+    
+    for (x, y) in page_corner_points(hocr_page_width, hocr_page_height, transform) {
+        let corner = js_sys::Object::new();
+        js_sys::Reflect::set(&corner, &"x".into(), &x.into()).unwrap();
+        js_sys::Reflect::set(&corner, &"y".into(), &y.into()).unwrap();
+        corners.push(&corner);
+    }
+    
+    corners
+}
+
 /// Parse color string to RGB values (enhanced from TypeScript implementation)
 #[wasm_bindgen]
 pub fn parse_color(color_string: &str) -> Option<Box<[f64]>> {
     // Handle hex colors (#ff0000, #f00)
-    if color_string.starts_with('#') {
-        let hex = &color_string[1..];
+    if let Some(hex) = color_string.strip_prefix('#') {
         let (r, g, b) = if hex.len() == 3 {
             let r = u8::from_str_radix(&hex[0..1].repeat(2), 16).ok()?;
             let g = u8::from_str_radix(&hex[1..2].repeat(2), 16).ok()?;
@@ -253,7 +344,52 @@ pub fn parse_color(color_string: &str) -> Option<Box<[f64]>> {
     })
 }
 
+/// Color seen when `r, g, b` (0–1) is drawn at `opacity` over a white page
+/// Returns `[r, g, b]`, e.g. for checking text contrast under a translucent highlight
+#[wasm_bindgen]
+pub fn blend_over_white(r: f64, g: f64, b: f64, opacity: f64) -> Box<[f64]> {
+    let alpha = opacity.clamp(0.0, 1.0);
+    let blend = |channel: f64| channel * alpha + (1.0 - alpha);
+    
+    Box::new([blend(r), blend(g), blend(b)])
+}
+
+/// WCAG contrast ratio (1–21) between two `[r, g, b]` colors with 0–1 channels
+#[wasm_bindgen]
+pub fn contrast_ratio(fg: &[f64], bg: &[f64]) -> f64 {
+    let (a, b) = (relative_luminance(fg), relative_luminance(bg));
+    let (lighter, darker) = if a >= b { (a, b) } else { (b, a) };
+    
+    (lighter + 0.05) / (darker + 0.05)
+}
+
+/// WCAG relative luminance of an sRGB color; missing channels count as 0
+fn relative_luminance(color: &[f64]) -> f64 {
+    let linear = |i: usize| {
+        let c = color.get(i).copied().unwrap_or(0.0).clamp(0.0, 1.0);
+        if c <= 0.03928 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+    };
+    
+    0.2126 * linear(0) + 0.7152 * linear(1) + 0.0722 * linear(2)
+}
+
+/// Minimum WCAG AA contrast ratio for normal-size text
+pub const WCAG_AA_CONTRAST: f64 = 4.5;
+
+/// Whether a style's label text is readable over its fill at WCAG AA (4.5:1)
+/// The fill is taken as it appears over a white page at the style's opacity
+#[wasm_bindgen]
+pub fn style_meets_aa(style: &AnnotationStyle) -> bool {
+    let fill = blend_over_white(style.fill_color_r, style.fill_color_g, style.fill_color_b, style.opacity);
+    let font = [style.font_color_r, style.font_color_g, style.font_color_b];
+    
+    contrast_ratio(&font, &fill) >= WCAG_AA_CONTRAST
+}
+
 /// Create annotation style with custom colors
+///
+/// `opacity` is clamped to the 0.0–1.0 range. Negative (or NaN) `border_width`
+/// and `font_size` values cannot be rendered and cause `None` to be returned.
 #[wasm_bindgen]
 pub fn create_custom_annotation_style(
     border_color: &str,
@@ -262,6 +398,11 @@ pub fn create_custom_annotation_style(
     border_width: f64,
     font_size: f64,
 ) -> Option<AnnotationStyle> {
+    if border_width.is_nan() || border_width < 0.0 || font_size.is_nan() || font_size < 0.0 {
+        return None;
+    }
+    let opacity = if opacity.is_nan() { 1.0 } else { opacity.clamp(0.0, 1.0) };
+    
     let border_rgb = parse_color(border_color)?;
     
     let mut style = AnnotationStyle {
@@ -292,6 +433,7 @@ pub fn create_custom_annotation_style(
 
 /// Generate annotation data for a given bounding box and match information
 /// This is the main function that JavaScript/TypeScript can call to get annotation data
+#[allow(clippy::too_many_arguments)]
 pub fn create_annotation_data(
     x1: f64,
     y1: f64,
@@ -312,6 +454,7 @@ pub fn create_annotation_data(
             "highlight" => AnnotationStyle::highlight_style(),
             "underline" => AnnotationStyle::underline_style(),
             "strikethrough" => AnnotationStyle::strikethrough_style(),
+            "note" => AnnotationStyle::note_style(),
             _ => AnnotationStyle::rectangle_style(),
         }
     });
@@ -325,18 +468,115 @@ pub fn create_annotation_data(
     )
 }
 
+/// Generate a fixed-size note icon anchored at the match's top-left corner
+/// Only `x1, y1` of the hOCR box are used; `label` becomes the popup text
+pub fn create_note_annotation(
+    x1: f64,
+    y1: f64,
+    transform: &CoordinateTransform,
+    style: Option<AnnotationStyle>,
+    label: &str,
+) -> AnnotationData {
+    // PDF rectangles grow upwards from y, so hang the icon below the anchor
+    let x = x1 * transform.scale_x;
+    let top = transform.page_height - (y1 * transform.scale_y);
+    let coordinates = PDFCoordinates::new(x, top - NOTE_ICON_SIZE, NOTE_ICON_SIZE, NOTE_ICON_SIZE);
+    
+    AnnotationData::new(
+        "note".to_string(),
+        coordinates,
+        style.unwrap_or_else(AnnotationStyle::note_style),
+        1.0,
+        label.to_string(),
+    )
+}
+
+/// Default padding in PDF points for an annotation type when `pad_by_type` is set
+/// Highlights hug the text; rectangles get some breathing room
+pub fn default_padding_for_type(annotation_type: &str) -> f64 {
+    match annotation_type {
+        "highlight" => 1.0,
+        "rectangle" => 3.0,
+        _ => 0.0,
+    }
+}
+
+/// Grow the annotation's PDF rectangle by `padding` points on every side
+pub fn pad_annotation(annotation: &mut AnnotationData, padding: f64) {
+    let coordinates = &mut annotation.coordinates;
+    coordinates.x -= padding;
+    coordinates.y -= padding;
+    coordinates.width += 2.0 * padding;
+    coordinates.height += 2.0 * padding;
+}
+
+/// Intersection over union of two PDF rectangles (0.0 when disjoint or degenerate)
+pub fn box_iou(a: &PDFCoordinates, b: &PDFCoordinates) -> f64 {
+    let overlap_width = (a.x + a.width).min(b.x + b.width) - a.x.max(b.x);
+    let overlap_height = (a.y + a.height).min(b.y + b.height) - a.y.max(b.y);
+    if overlap_width <= 0.0 || overlap_height <= 0.0 {
+        return 0.0;
+    }
+    
+    let intersection = overlap_width * overlap_height;
+    let union = a.width * a.height + b.width * b.height - intersection;
+    if union <= 0.0 {
+        return 0.0;
+    }
+    intersection / union
+}
+
+/// Collapse annotations whose boxes are near-identical (IoU above 0.95)
+/// The highest-similarity annotation of each group survives (the earliest on ties),
+/// and survivors keep their input order
+pub fn dedupe_annotations_by_box(annotations: Vec<AnnotationData>) -> Vec<AnnotationData> {
+    let mut kept: Vec<AnnotationData> = Vec::new();
+    
+    for annotation in annotations {
+        match kept.iter_mut().find(|k| box_iou(&k.coordinates, &annotation.coordinates) > DEDUPE_IOU_THRESHOLD) {
+            Some(existing) if annotation.similarity_score > existing.similarity_score => *existing = annotation,
+            Some(_) => {}
+            None => kept.push(annotation),
+        }
+    }
+    
+    kept
+}
+
+/// IoU above which two annotation boxes count as duplicates
+const DEDUPE_IOU_THRESHOLD: f64 = 0.95;
+
+/// Round `x`, `y`, `width` and `height` to `decimals` decimal places
+pub fn round_coordinates(coordinates: &mut PDFCoordinates, decimals: u32) {
+    let factor = 10f64.powi(decimals as i32);
+    let round = |value: f64| (value * factor).round() / factor;
+    
+    coordinates.x = round(coordinates.x);
+    coordinates.y = round(coordinates.y);
+    coordinates.width = round(coordinates.width);
+    coordinates.height = round(coordinates.height);
+}
+
 /// Batch process multiple annotations
+/// Each box may carry a `zIndex` (default 0); the output is sorted by it, ascending
+/// With `pad_by_type`, boxes grow by `default_padding_for_type` points per side unless
+/// the box carries its own `padding`; with `dedupe_by_box`, near-identical boxes
+/// collapse to the one with the highest similarity; with `round_to`, coordinates are
+/// rounded to that many decimal places
 #[wasm_bindgen]
 pub fn create_multiple_annotations(
     bounding_boxes: &js_sys::Array,
     transform: &CoordinateTransform,
     annotation_type: &str,
     custom_style: Option<AnnotationStyle>,
+    pad_by_type: bool,
+    dedupe_by_box: bool,
+    round_to: Option<u32>,
 ) -> js_sys::Array {
-    let results = js_sys::Array::new();
+    let mut annotations = Vec::new();
     
     for i in 0..bounding_boxes.length() {
-        if let Some(bbox_obj) = bounding_boxes.get(i).dyn_into::<js_sys::Object>().ok() {
+        if let Ok(bbox_obj) = bounding_boxes.get(i).dyn_into::<js_sys::Object>() {
             // Extract bounding box values
             let x1 = js_sys::Reflect::get(&bbox_obj, &"x1".into())
                 .ok()
@@ -361,46 +601,168 @@ pub fn create_multiple_annotations(
             let text = js_sys::Reflect::get(&bbox_obj, &"text".into())
                 .ok()
                 .and_then(|v| v.as_string())
-                .unwrap_or_else(|| "".to_string());
+                .unwrap_or_default();
+            let z_index = js_sys::Reflect::get(&bbox_obj, &"zIndex".into())
+                .ok()
+                .and_then(|v| v.as_f64())
+                .map_or(0, |z| z as i32);
+            let padding = js_sys::Reflect::get(&bbox_obj, &"padding".into())
+                .ok()
+                .and_then(|v| v.as_f64());
             
-            let annotation = create_annotation_data(
+            let mut annotation = create_annotation_data(
                 x1, y1, x2, y2,
                 transform,
                 annotation_type,
                 similarity,
                 &text,
-                custom_style.clone(),
+                custom_style,
             );
-            
-            // Convert to JavaScript object
-            let js_annotation = js_sys::Object::new();
-            js_sys::Reflect::set(&js_annotation, &"annotationType".into(), &annotation.annotation_type.into()).unwrap();
-            js_sys::Reflect::set(&js_annotation, &"x".into(), &annotation.coordinates.x.into()).unwrap();
-            js_sys::Reflect::set(&js_annotation, &"y".into(), &annotation.coordinates.y.into()).unwrap();
-            js_sys::Reflect::set(&js_annotation, &"width".into(), &annotation.coordinates.width.into()).unwrap();
-            js_sys::Reflect::set(&js_annotation, &"height".into(), &annotation.coordinates.height.into()).unwrap();
-            js_sys::Reflect::set(&js_annotation, &"similarityScore".into(), &annotation.similarity_score.into()).unwrap();
-            js_sys::Reflect::set(&js_annotation, &"matchedText".into(), &annotation.matched_text.into()).unwrap();
-            
-            // Add style information
-            let style_obj = js_sys::Object::new();
-            js_sys::Reflect::set(&style_obj, &"borderColorR".into(), &annotation.style.border_color_r.into()).unwrap();
-            js_sys::Reflect::set(&style_obj, &"borderColorG".into(), &annotation.style.border_color_g.into()).unwrap();
-            js_sys::Reflect::set(&style_obj, &"borderColorB".into(), &annotation.style.border_color_b.into()).unwrap();
-            js_sys::Reflect::set(&style_obj, &"fillColorR".into(), &annotation.style.fill_color_r.into()).unwrap();
-            js_sys::Reflect::set(&style_obj, &"fillColorG".into(), &annotation.style.fill_color_g.into()).unwrap();
-            js_sys::Reflect::set(&style_obj, &"fillColorB".into(), &annotation.style.fill_color_b.into()).unwrap();
-            js_sys::Reflect::set(&style_obj, &"opacity".into(), &annotation.style.opacity.into()).unwrap();
-            js_sys::Reflect::set(&style_obj, &"borderWidth".into(), &annotation.style.border_width.into()).unwrap();
-            js_sys::Reflect::set(&style_obj, &"fontSize".into(), &annotation.style.font_size.into()).unwrap();
-            
-            js_sys::Reflect::set(&js_annotation, &"style".into(), &style_obj).unwrap();
-            
-            results.push(&js_annotation);
+            annotation.z_index = z_index;
+            if pad_by_type || padding.is_some() {
+                pad_annotation(&mut annotation, padding.unwrap_or_else(|| default_padding_for_type(annotation_type)));
+            }
+            annotations.push(annotation);
+        }
+    }
+    
+    if dedupe_by_box {
+        annotations = dedupe_annotations_by_box(annotations);
+    }
+    sort_annotations_by_z_index(&mut annotations);
+    if let Some(decimals) = round_to {
+        for annotation in &mut annotations {
+            round_coordinates(&mut annotation.coordinates, decimals);
+        }
+    }
+    
+    let outputs: Vec<AnnotationOutput> = annotations.iter().map(AnnotationOutput::from).collect();
+    serde_wasm_bindgen::to_value(&outputs).unwrap().unchecked_into()
+}
+
+/// Match a search string in hOCR and build its annotation in PDF space
+/// None when the string has no match
+pub fn match_annotation(
+    hocr_content: &str,
+    search_string: &str,
+    transform: &CoordinateTransform,
+    annotation_type: &str,
+    custom_style: Option<AnnotationStyle>,
+) -> Option<AnnotationData> {
+    let result = find_hocr_match(hocr_content, search_string, &ExtractOptions::default())?;
+    
+    Some(create_annotation_data(
+        result.bbox.x1, result.bbox.y1, result.bbox.x2, result.bbox.y2,
+        transform,
+        annotation_type,
+        result.similarity,
+        &result.matched_text,
+        custom_style,
+    ))
+}
+
+/// One-shot pipeline: parse hOCR, match, transform and build the annotation object
+/// Returns the same shape as each item of `create_multiple_annotations`
+#[wasm_bindgen]
+pub fn annotate_match(
+    hocr_content: &str,
+    search_string: &str,
+    transform: &CoordinateTransform,
+    annotation_type: &str,
+    custom_style: Option<AnnotationStyle>,
+) -> Option<js_sys::Object> {
+    match_annotation(hocr_content, search_string, transform, annotation_type, custom_style)
+        .map(|annotation| annotation_to_js(&annotation))
+}
+
+/// Annotations for each matched search string, in query order; with `per_word` each
+/// matched word gets its own annotation (carrying the phrase similarity) instead of
+/// one per phrase. Strings without a match are skipped
+pub fn match_annotations(
+    hocr_content: &str,
+    search_strings: &[String],
+    transform: &CoordinateTransform,
+    annotation_type: &str,
+    per_word: bool,
+) -> Vec<AnnotationData> {
+    let mut annotations = Vec::new();
+    for search_string in search_strings {
+        if !per_word {
+            annotations.extend(match_annotation(hocr_content, search_string, transform, annotation_type, None));
+            continue;
+        }
+        let Some((regions, similarity)) = matched_word_regions(hocr_content, search_string) else {
+            continue;
+        };
+        for (text, bbox) in regions {
+            annotations.push(create_annotation_data(
+                bbox.x1, bbox.y1, bbox.x2, bbox.y2,
+                transform,
+                annotation_type,
+                similarity,
+                &text,
+                None,
+            ));
         }
     }
     
-    results
+    annotations
+}
+
+/// Batch-annotate search strings in hOCR, one annotation per matched word when
+/// `per_word` is set (e.g. karaoke-style highlighting), otherwise one per phrase
+/// Items have the same shape as those of `create_multiple_annotations`
+#[wasm_bindgen]
+pub fn annotate_matches_per_word(
+    hocr_content: &str,
+    search_strings: &js_sys::Array,
+    transform: &CoordinateTransform,
+    annotation_type: &str,
+    per_word: bool,
+) -> js_sys::Array {
+    let search_strings: Vec<String> = search_strings.iter().filter_map(|v| v.as_string()).collect();
+    let outputs: Vec<AnnotationOutput> = match_annotations(hocr_content, &search_strings, transform, annotation_type, per_word)
+        .iter()
+        .map(AnnotationOutput::from)
+        .collect();
+    
+    serde_wasm_bindgen::to_value(&outputs).unwrap().unchecked_into()
+}
+
+/// Flat JS shape of an annotation: coordinates inlined, camelCase keys
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotationOutput {
+    pub annotation_type: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub similarity_score: f64,
+    pub matched_text: String,
+    pub z_index: i32,
+    pub style: AnnotationStyle,
+}
+
+impl From<&AnnotationData> for AnnotationOutput {
+    fn from(annotation: &AnnotationData) -> AnnotationOutput {
+        AnnotationOutput {
+            annotation_type: annotation.annotation_type.clone(),
+            x: annotation.coordinates.x,
+            y: annotation.coordinates.y,
+            width: annotation.coordinates.width,
+            height: annotation.coordinates.height,
+            similarity_score: annotation.similarity_score,
+            matched_text: annotation.matched_text.clone(),
+            z_index: annotation.z_index,
+            style: annotation.style,
+        }
+    }
+}
+
+/// Convert annotation data to the plain object handed to the JS renderer
+fn annotation_to_js(annotation: &AnnotationData) -> js_sys::Object {
+    serde_wasm_bindgen::to_value(&AnnotationOutput::from(annotation)).unwrap().unchecked_into()
 }
 
 #[cfg(test)]
@@ -452,4 +814,222 @@ mod tests {
         assert_eq!(highlight_style.border_color_g, 1.0);
         assert_eq!(highlight_style.opacity, 0.3);
     }
+
+    #[test]
+    fn test_custom_style_clamps_opacity() {
+        let style = create_custom_annotation_style("red", None, 1.7, 2.0, 10.0).unwrap();
+        assert_eq!(style.opacity, 1.0);
+        
+        let style = create_custom_annotation_style("red", None, -0.5, 2.0, 10.0).unwrap();
+        assert_eq!(style.opacity, 0.0);
+    }
+
+    #[test]
+    fn test_custom_style_rejects_negative_sizes() {
+        assert!(create_custom_annotation_style("red", None, 0.5, 2.0, -1.0).is_none());
+        assert!(create_custom_annotation_style("red", None, 0.5, -2.0, 10.0).is_none());
+    }
+
+    #[test]
+    fn test_coordinate_transform_from_dpi() {
+        let transform = coordinate_transform_from_dpi(300.0, 792.0);
+        assert!((transform.scale_x - 0.24).abs() < 1e-9);
+        assert!((transform.scale_y - 0.24).abs() < 1e-9);
+        assert_eq!(transform.page_height, 792.0);
+    }
+
+    #[test]
+    fn test_sort_annotations_by_z_index() {
+        let transform = CoordinateTransform::new(1.0, 1.0, 0.0, 0.0, 800.0);
+        let mut annotations: Vec<AnnotationData> = [(2, "top"), (0, "base"), (-1, "under"), (0, "base2")]
+            .iter()
+            .map(|&(z_index, text)| {
+                let mut annotation = create_annotation_data(0.0, 0.0, 10.0, 10.0, &transform, "rectangle", 1.0, text, None);
+                annotation.z_index = z_index;
+                annotation
+            })
+            .collect();
+        
+        sort_annotations_by_z_index(&mut annotations);
+        let order: Vec<&str> = annotations.iter().map(|a| a.matched_text.as_str()).collect();
+        assert_eq!(order, vec!["under", "base", "base2", "top"]);
+    }
+
+    #[test]
+    fn test_create_note_annotation_fixed_size_at_top_left() {
+        let transform = CoordinateTransform::new(0.5, 0.5, 0.0, 0.0, 800.0);
+        let note = create_note_annotation(100.0, 200.0, &transform, None, "Check this figure");
+        
+        assert_eq!(note.annotation_type, "note");
+        assert_eq!(note.matched_text, "Check this figure");
+        assert_eq!((note.coordinates.width, note.coordinates.height), (NOTE_ICON_SIZE, NOTE_ICON_SIZE));
+        // Top-left of the hOCR box maps to (50, 700); the icon hangs below it
+        assert_eq!(note.coordinates.x, 50.0);
+        assert_eq!(note.coordinates.y + note.coordinates.height, 700.0);
+        assert_eq!(note.style.fill_color_g, AnnotationStyle::note_style().fill_color_g);
+    }
+
+    #[test]
+    fn test_page_corner_points() {
+        let transform = calculate_coordinate_transform(612.0, 792.0, 2550.0, 3300.0);
+        let corners = page_corner_points(2550.0, 3300.0, &transform);
+        
+        // hOCR top-left (origin at the top) is PDF top-left (origin at the bottom)
+        assert_eq!(corners[0], (0.0, 792.0));
+        assert!((corners[1].0 - 612.0).abs() < 1e-9 && corners[1].1 == 792.0);
+        assert!((corners[2].0 - 612.0).abs() < 1e-9 && corners[2].1.abs() < 1e-9);
+        assert!(corners[3].0 == 0.0 && corners[3].1.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pad_by_type_inflates_rectangles_more() {
+        let transform = CoordinateTransform::new(1.0, 1.0, 0.0, 0.0, 800.0);
+        let padded = |annotation_type: &str| {
+            let mut annotation = create_annotation_data(100.0, 100.0, 200.0, 120.0, &transform, annotation_type, 1.0, "", None);
+            pad_annotation(&mut annotation, default_padding_for_type(annotation_type));
+            annotation.coordinates
+        };
+        
+        let rectangle = padded("rectangle");
+        let highlight = padded("highlight");
+        assert_eq!((rectangle.x, rectangle.width, rectangle.height), (97.0, 106.0, 26.0));
+        assert_eq!((highlight.x, highlight.width, highlight.height), (99.0, 102.0, 22.0));
+        assert_eq!(padded("underline").width, 100.0);
+    }
+
+    #[test]
+    fn test_dedupe_annotations_by_box() {
+        let transform = CoordinateTransform::new(1.0, 1.0, 0.0, 0.0, 800.0);
+        let annotation = |x1: f64, similarity: f64, text: &str| {
+            create_annotation_data(x1, 100.0, x1 + 200.0, 150.0, &transform, "highlight", similarity, text, None)
+        };
+        
+        let deduped = dedupe_annotations_by_box(vec![
+            annotation(100.0, 0.9, "rule a"),
+            annotation(101.0, 1.0, "rule b"),
+            annotation(400.0, 0.8, "elsewhere"),
+        ]);
+        let texts: Vec<&str> = deduped.iter().map(|a| a.matched_text.as_str()).collect();
+        assert_eq!(texts, vec!["rule b", "elsewhere"]);
+        
+        let a = PDFCoordinates::new(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(box_iou(&a, &a), 1.0);
+        assert_eq!(box_iou(&a, &PDFCoordinates::new(5.0, 0.0, 10.0, 10.0)), 50.0 / 150.0);
+    }
+
+    #[test]
+    fn test_round_coordinates_to_two_decimals() {
+        let transform = calculate_coordinate_transform(612.0, 792.0, 2550.0, 3300.0);
+        let mut coordinates = transform_coordinates(101.3, 203.7, 457.9, 251.1, &transform);
+        assert_ne!(coordinates.x, 24.31);
+        
+        round_coordinates(&mut coordinates, 2);
+        assert_eq!((coordinates.x, coordinates.y), (24.31, 731.74));
+        assert_eq!((coordinates.width, coordinates.height), (85.58, 11.38));
+    }
+
+    #[test]
+    fn test_match_annotation_runs_full_pipeline() {
+        let hocr_sample = r#"
+        <div class='ocr_page' title='bbox 0 0 2550 3300'>
+        <span class='ocr_line' title='bbox 100 200 700 250'>
+            <span class='ocrx_word' title='bbox 100 200 300 250'>Amount</span>
+            <span class='ocrx_word' title='bbox 320 200 450 250'>due</span>
+        </span>
+        </div>"#;
+        let transform = calculate_coordinate_transform(612.0, 792.0, 2550.0, 3300.0);
+        
+        let annotation = match_annotation(hocr_sample, "amount due", &transform, "highlight", None).unwrap();
+        assert_eq!(annotation.annotation_type, "highlight");
+        assert_eq!(annotation.matched_text, "Amount due");
+        assert_eq!(annotation.similarity_score, 1.0);
+        assert!((annotation.coordinates.x - 24.0).abs() < 1e-9);
+        assert!((annotation.coordinates.y - 732.0).abs() < 1e-9);
+        assert!((annotation.coordinates.width - 84.0).abs() < 1e-9);
+        assert_eq!(annotation.style.opacity, AnnotationStyle::highlight_style().opacity);
+        
+        assert!(match_annotation(hocr_sample, "nowhere near", &transform, "highlight", None).is_none());
+    }
+
+    #[test]
+    fn test_blend_over_white_translucent_yellow() {
+        let blended = blend_over_white(1.0, 1.0, 0.0, 0.3);
+        assert_eq!(blended[0], 1.0);
+        assert_eq!(blended[1], 1.0);
+        assert!((blended[2] - 0.7).abs() < 1e-9);
+        
+        assert_eq!(&*blend_over_white(0.2, 0.4, 0.6, 1.0), &[0.2, 0.4, 0.6]);
+        assert_eq!(&*blend_over_white(0.2, 0.4, 0.6, 0.0), &[1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_contrast_ratio_and_aa_check() {
+        assert!((contrast_ratio(&[0.0, 0.0, 0.0], &[1.0, 1.0, 1.0]) - 21.0).abs() < 1e-9);
+        assert_eq!(contrast_ratio(&[1.0, 1.0, 1.0], &[0.0, 0.0, 0.0]), contrast_ratio(&[0.0, 0.0, 0.0], &[1.0, 1.0, 1.0]));
+        assert!((contrast_ratio(&[0.5, 0.5, 0.5], &[0.5, 0.5, 0.5]) - 1.0).abs() < 1e-9);
+        
+        // Black label on an opaque pale-yellow note passes
+        assert!(style_meets_aa(&AnnotationStyle::note_style()));
+        // Dark-yellow label over a translucent yellow highlight does not
+        assert!(!style_meets_aa(&AnnotationStyle::highlight_style()));
+    }
+
+    #[test]
+    fn test_pdf_line_height_from_even_lines() {
+        let hocr_sample = r#"
+        <span class='ocr_line' title='bbox 100 100 900 140'><span class='ocrx_word' title='bbox 100 100 200 140'>one</span></span>
+        <span class='ocr_line' title='bbox 100 150 900 190'><span class='ocrx_word' title='bbox 100 150 200 190'>two</span></span>
+        <span class='ocr_line' title='bbox 100 200 900 240'><span class='ocrx_word' title='bbox 100 200 200 240'>three</span></span>
+        <span class='ocr_line' title='bbox 100 250 900 290'><span class='ocrx_word' title='bbox 100 250 200 290'>four</span></span>"#;
+        let transform = coordinate_transform_from_dpi(300.0, 792.0);
+        
+        assert!((pdf_line_height(hocr_sample, &transform).unwrap() - 12.0).abs() < 1e-9);
+        assert!(pdf_line_height("<span class='ocr_line' title='bbox 0 0 10 10'></span>", &transform).is_none());
+    }
+
+    #[test]
+    fn test_match_annotations_per_word() {
+        let hocr_sample = r#"
+        <div class='ocr_page' title='bbox 0 0 2550 3300'>
+        <span class='ocr_line' title='bbox 100 200 900 250'>
+            <span class='ocrx_word' title='bbox 100 200 300 250'>Total</span>
+            <span class='ocrx_word' title='bbox 320 200 450 250'>amount</span>
+            <span class='ocrx_word' title='bbox 470 200 560 250'>due</span>
+        </span>
+        </div>"#;
+        let transform = calculate_coordinate_transform(612.0, 792.0, 2550.0, 3300.0);
+        let queries = vec!["total amount due".to_string()];
+        
+        let words = match_annotations(hocr_sample, &queries, &transform, "highlight", true);
+        assert_eq!(words.len(), 3);
+        let texts: Vec<&str> = words.iter().map(|a| a.matched_text.as_str()).collect();
+        assert_eq!(texts, vec!["Total", "amount", "due"]);
+        assert!((words[1].coordinates.x - 320.0 * 0.24).abs() < 1e-9);
+        
+        let phrases = match_annotations(hocr_sample, &queries, &transform, "highlight", false);
+        assert_eq!(phrases.len(), 1);
+        assert_eq!(phrases[0].matched_text, "Total amount due");
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    #[wasm_bindgen_test::wasm_bindgen_test]
+    fn test_annotation_output_round_trips_through_js() {
+        let transform = calculate_coordinate_transform(612.0, 792.0, 2550.0, 3300.0);
+        let annotation = create_annotation_data(100.0, 200.0, 300.0, 250.0, &transform, "highlight", 1.0, "Amount", None);
+        let js_object = annotation_to_js(&annotation);
+        
+        // The keys the renderer reads
+        for key in ["annotationType", "similarityScore", "matchedText", "zIndex"] {
+            assert!(js_sys::Reflect::has(&js_object, &key.into()).unwrap(), "{}", key);
+        }
+        let style = js_sys::Reflect::get(&js_object, &"style".into()).unwrap();
+        assert!(js_sys::Reflect::has(&style, &"borderColorR".into()).unwrap());
+        
+        let output: AnnotationOutput = serde_wasm_bindgen::from_value(js_object.into()).unwrap();
+        assert_eq!(output.annotation_type, "highlight");
+        assert_eq!(output.matched_text, "Amount");
+        assert_eq!((output.x, output.width), (annotation.coordinates.x, annotation.coordinates.width));
+        assert_eq!(output.z_index, annotation.z_index);
+        assert_eq!(output.style.border_width, annotation.style.border_width);
+    }
 }
\ No newline at end of file
@@ -1,5 +1,9 @@
 use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::cmp;
+use std::collections::HashMap;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
 
 #[wasm_bindgen]
 #[derive(Debug, Clone)]
@@ -15,6 +19,23 @@ pub struct MatchResult {
     pub debug_search_words: String,
     pub debug_cleaned_word_count: usize,
     pub debug_search_word_count: usize,
+    /// Number of cleaned words the match spans (`end_index - start_index`), which can
+    /// differ from the search word count for variable-size windows or merged words
+    pub window_size: usize,
+    #[wasm_bindgen(skip)]
+    pub unmatched_search_words: Vec<usize>,
+    /// Lowest-scoring aligned word pair of a fuzzy match; None for exact-pass results
+    #[wasm_bindgen(skip)]
+    pub weakest_word: Option<WeakestWord>,
+}
+
+/// The matched word at `position` (counted from the start of the match) that scored
+/// lowest against the search word aligned with it
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeakestWord {
+    pub word: String,
+    pub position: usize,
+    pub similarity: f64,
 }
 
 #[wasm_bindgen]
@@ -33,8 +54,234 @@ impl MatchResult {
     pub fn debug_search_words(&self) -> String {
         self.debug_search_words.clone()
     }
+    
+    /// Indices of search words with no counterpart in the match scoring at least
+    /// `UNMATCHED_WORD_THRESHOLD`
+    #[wasm_bindgen(getter = unmatchedSearchWords)]
+    pub fn unmatched_search_words(&self) -> Vec<u32> {
+        self.unmatched_search_words.iter().map(|&i| i as u32).collect()
+    }
+    
+    /// `{word, position, similarity}` of the weakest aligned word in a fuzzy match,
+    /// for prioritizing manual review
+    #[wasm_bindgen(getter = weakestWord)]
+    pub fn weakest_word(&self) -> Option<js_sys::Object> {
+        self.weakest_word.as_ref().map(|weakest| {
+            let js_word = js_sys::Object::new();
+            js_sys::Reflect::set(&js_word, &"word".into(), &weakest.word.as_str().into()).unwrap();
+            js_sys::Reflect::set(&js_word, &"position".into(), &(weakest.position as u32).into()).unwrap();
+            js_sys::Reflect::set(&js_word, &"similarity".into(), &weakest.similarity.into()).unwrap();
+            js_word
+        })
+    }
+    
+    /// Number of words in the matched text
+    #[wasm_bindgen(getter = matchedWordCount)]
+    pub fn matched_word_count(&self) -> usize {
+        self.text.split_whitespace().count()
+    }
+    
+    /// Number of characters in the matched text, including the spaces between words
+    #[wasm_bindgen(getter = matchedCharCount)]
+    pub fn matched_char_count(&self) -> usize {
+        self.text.chars().count()
+    }
+}
+
+/// How a window of words is compared against the search words
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SimilarityMetric {
+    /// Fraction of positions holding the same word (`sequence_similarity`)
+    #[default]
+    Positional,
+    /// Longest common subsequence of words over the longer length (`lcs_word_similarity`)
+    WordLcs,
+    /// Positional, with short tokens counting less (`length_weighted_similarity`)
+    LengthWeighted,
+}
+
+/// How per-word similarities combine into a window score in the fuzzy pass
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Aggregation {
+    /// Average over positions
+    #[default]
+    Mean,
+    /// Worst word; one poor word gates the whole window
+    Min,
+    /// Harmonic mean; dominated by the weakest words, 0 if any word scores 0
+    Harmonic,
+}
+
+/// Tunable behaviour for the sliding-window matcher
+/// The defaults reproduce `find_closest_match` exactly
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct MatchOptions {
+    /// Give partial credit when two adjacent words appear in swapped order
+    pub allow_adjacent_swaps: bool,
+    /// How many words wider than the search the fuzzy pass may look; when set,
+    /// wider windows are scored by in-order alignment so interspersed tokens are skipped
+    pub max_extra_words: Option<usize>,
+    /// Window scoring used by the exact (first) pass
+    pub metric: SimilarityMetric,
+    /// Also try hyphenated search words joined ("email") and split ("e mail")
+    pub flexible_hyphens: bool,
+    /// Only credit whole-word equality in the fuzzy pass, so "123" never
+    /// partially matches "12345" via substring or character overlap
+    pub whole_word_only: bool,
+    /// Ignore purely numeric tokens (page numbers, footnote markers) on both sides;
+    /// result indices still refer to the unfiltered cleaned words
+    pub drop_numeric_tokens: bool,
+    /// Treat curly quotes as straight quotes and en/em dashes as hyphens on both sides
+    pub normalize_punctuation_variants: bool,
+    /// Strip thousands separators on both sides, so "1,000,000", "1000000" and
+    /// "1 000 000" all compare equal; result indices still refer to the cleaned words
+    pub normalize_number_grouping: bool,
+    /// How per-word similarities combine into a window score; anything but `Mean` also
+    /// caps exact-pass scores and turns off character-overlap scoring, so the weakest
+    /// word gates the match in both passes
+    pub aggregation: Aggregation,
+    /// Minimum score (exclusive) for the fuzzy pass to accept a window
+    pub fuzzy_accept_threshold: f64,
+    /// Word similarity credited when one word contains the other
+    pub substring_credit: f64,
+    /// Scale window scores by `1 - |len_diff| / max_len` over the characters of the window
+    /// and the search, for fixed-format codes where length is meaningful
+    pub length_penalty: bool,
+    /// Score a word that is a prefix or suffix of the other (an OCR-truncated word) by
+    /// overlap length over the longer length instead of `substring_credit`
+    pub affix_overlap_credit: bool,
+    /// Accepted alternatives per lowercase word; aliased pairs count as exact matches
+    #[wasm_bindgen(skip)]
+    pub aliases: HashMap<String, Vec<String>>,
+    /// Only windows whose first word equals this (case-insensitively) are considered
+    #[wasm_bindgen(skip)]
+    pub require_prefix: Option<String>,
+    /// Reject windows with any of these words among the `EXCLUSION_CONTEXT_WORDS` words
+    /// on either side (case-insensitive, punctuation ignored)
+    #[wasm_bindgen(skip)]
+    pub exclude_terms: Vec<String>,
+    /// Keep LINE markers as hard boundaries; when false, a search word may also match a
+    /// word broken across a line ("inter-" / "national"), reported as both pieces
+    pub respect_line_boundaries: bool,
+    /// Also try adjacent tokens concatenated ("docu ment") as one search word, for OCR
+    /// that inserted spaces inside words
+    pub rejoin_split_words: bool,
+}
+
+impl Default for MatchOptions {
+    fn default() -> Self {
+        MatchOptions {
+            allow_adjacent_swaps: false,
+            max_extra_words: None,
+            metric: SimilarityMetric::default(),
+            flexible_hyphens: false,
+            whole_word_only: false,
+            drop_numeric_tokens: false,
+            normalize_punctuation_variants: false,
+            normalize_number_grouping: false,
+            aggregation: Aggregation::default(),
+            fuzzy_accept_threshold: DEFAULT_FUZZY_ACCEPT_THRESHOLD,
+            substring_credit: DEFAULT_SUBSTRING_CREDIT,
+            affix_overlap_credit: false,
+            length_penalty: false,
+            aliases: HashMap::new(),
+            require_prefix: None,
+            exclude_terms: Vec::new(),
+            respect_line_boundaries: true,
+            rejoin_split_words: false,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl MatchOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> MatchOptions {
+        MatchOptions::default()
+    }
+    
+    /// Accept `alternative` wherever `word` is expected (and vice versa)
+    pub fn add_alias(&mut self, word: &str, alternative: &str) {
+        self.aliases.entry(word.to_lowercase())
+            .or_default()
+            .push(alternative.to_lowercase());
+    }
+    
+    /// Only accept windows starting with `prefix` (case-insensitive); None lifts the requirement
+    pub fn set_require_prefix(&mut self, prefix: Option<String>) {
+        self.require_prefix = prefix;
+    }
+}
+
+impl MatchOptions {
+    /// Exact word equality, extended by the alias table (looked up case-insensitively;
+    /// aliases never make otherwise unrelated comparisons case-insensitive)
+    fn words_equal(&self, word1: &str, word2: &str) -> bool {
+        if word1 == word2 {
+            return true;
+        }
+        if self.aliases.is_empty() {
+            return false;
+        }
+        
+        let (word1, word2) = (word1.to_lowercase(), word2.to_lowercase());
+        let listed = |key: &str, alternative: &str| {
+            self.aliases.get(key).is_some_and(|alternatives| alternatives.iter().any(|a| a == alternative))
+        };
+        listed(&word1, &word2) || listed(&word2, &word1)
+    }
+    
+    /// Whether the `window_size` words of `words` at `start` satisfy `require_prefix`
+    /// and have no `exclude_terms` word nearby
+    fn window_allowed(&self, words: &[&str], start: usize, window_size: usize) -> bool {
+        if !self.prefix_allows(&words[start..start + window_size]) {
+            return false;
+        }
+        if self.exclude_terms.is_empty() {
+            return true;
+        }
+        
+        let comparable = |word: &str| word.trim_matches(|c: char| c.is_ascii_punctuation()).to_lowercase();
+        let excluded: Vec<String> = self.exclude_terms.iter().map(|term| comparable(term)).collect();
+        let before = start.saturating_sub(EXCLUSION_CONTEXT_WORDS)..start;
+        let after = start + window_size..cmp::min(words.len(), start + window_size + EXCLUSION_CONTEXT_WORDS);
+        !before.chain(after).any(|i| excluded.contains(&comparable(words[i])))
+    }
+    
+    /// Whether a window satisfies `require_prefix`
+    fn prefix_allows(&self, window: &[&str]) -> bool {
+        match (&self.require_prefix, window.first()) {
+            (None, _) => true,
+            (Some(prefix), Some(first)) => first.to_lowercase() == prefix.to_lowercase(),
+            (Some(_), None) => false,
+        }
+    }
 }
 
+/// Credit given to each word of a transposed adjacent pair
+const ADJACENT_SWAP_CREDIT: f64 = 0.75;
+
+/// Extra window words the fuzzy pass considers when `max_extra_words` is unset
+const DEFAULT_MAX_EXTRA_WORDS: usize = 3;
+
+/// Score penalty for each window word skipped by `gapped_sequence_similarity`
+const EXTRA_WORD_PENALTY: f64 = 0.1;
+
+/// Score a fuzzy-pass window must exceed unless `fuzzy_accept_threshold` is changed
+const DEFAULT_FUZZY_ACCEPT_THRESHOLD: f64 = 0.6;
+
+/// Substring word credit unless `substring_credit` is changed
+const DEFAULT_SUBSTRING_CREDIT: f64 = 0.8;
+
+/// Best word similarity below which a search word is reported as unmatched
+const UNMATCHED_WORD_THRESHOLD: f64 = 0.5;
+
+/// Upper bound on search variants generated by `flexible_hyphens`
+const MAX_HYPHEN_VARIANTS: usize = 27;
+
 /// Calculate sequence similarity between two sequences of words
 /// Ported from Python closest_match.py:sequence_similarity()
 pub fn sequence_similarity(seq1: &[&str], seq2: &[&str]) -> f64 {
@@ -51,24 +298,187 @@ pub fn sequence_similarity(seq1: &[&str], seq2: &[&str]) -> f64 {
     matching_chars as f64 / max_len as f64
 }
 
+/// Positional similarity where each position is weighted by `min(len, 4) / 4`
+/// (longer of the two words, in chars), so stray short tokens carry less weight
+pub fn length_weighted_similarity(seq1: &[&str], seq2: &[&str]) -> f64 {
+    length_weighted_similarity_by(seq1, seq2, |a, b| a == b)
+}
+
+/// Length-weighted positional similarity using a custom word equality
+fn length_weighted_similarity_by(seq1: &[&str], seq2: &[&str], words_equal: impl Fn(&str, &str) -> bool) -> f64 {
+    let positions = cmp::max(seq1.len(), seq2.len());
+    if positions == 0 {
+        return 1.0;
+    }
+    
+    let mut total_weight = 0.0;
+    let mut matched_weight = 0.0;
+    
+    for i in 0..positions {
+        let word1 = seq1.get(i).copied().unwrap_or("");
+        let word2 = seq2.get(i).copied().unwrap_or("");
+        let length = cmp::max(word1.chars().count(), word2.chars().count());
+        let weight = cmp::min(length, 4) as f64 / 4.0;
+        
+        total_weight += weight;
+        if i < seq1.len() && i < seq2.len() && words_equal(word1, word2) {
+            matched_weight += weight;
+        }
+    }
+    
+    if total_weight == 0.0 {
+        return 0.0;
+    }
+    matched_weight / total_weight
+}
+
+/// Word-level longest common subsequence length over the longer sequence length
+/// Tolerates dropped words as long as the remaining words keep their order
+pub fn lcs_word_similarity(seq1: &[&str], seq2: &[&str]) -> f64 {
+    lcs_similarity_by(seq1, seq2, |a, b| a == b)
+}
+
+/// Word LCS similarity using a custom word equality
+fn lcs_similarity_by(seq1: &[&str], seq2: &[&str], words_equal: impl Fn(&str, &str) -> bool) -> f64 {
+    if seq1.is_empty() && seq2.is_empty() {
+        return 1.0;
+    }
+    
+    // Single-row LCS table
+    let mut lengths = vec![0usize; seq2.len() + 1];
+    for word1 in seq1 {
+        let mut diagonal = 0;
+        for (j, word2) in seq2.iter().enumerate() {
+            let above = lengths[j + 1];
+            lengths[j + 1] = if words_equal(word1, word2) {
+                diagonal + 1
+            } else {
+                cmp::max(above, lengths[j])
+            };
+            diagonal = above;
+        }
+    }
+    
+    lengths[seq2.len()] as f64 / cmp::max(seq1.len(), seq2.len()) as f64
+}
+
+/// `sequence_similarity` with word equality taken from the options (aliases)
+fn positional_similarity(seq1: &[&str], seq2: &[&str], options: &MatchOptions) -> f64 {
+    if seq1.is_empty() && seq2.is_empty() {
+        return 1.0;
+    }
+    
+    let matching = seq1.iter()
+        .zip(seq2.iter())
+        .filter(|(a, b)| options.words_equal(a, b))
+        .count();
+    
+    matching as f64 / cmp::max(seq1.len(), seq2.len()) as f64
+}
+
+/// Positional similarity that also credits a local transposition, i.e.
+/// `seq1[i], seq1[i + 1]` equal to `seq2[i + 1], seq2[i]`
+fn swap_tolerant_similarity(seq1: &[&str], seq2: &[&str], options: &MatchOptions) -> f64 {
+    if seq1.is_empty() && seq2.is_empty() {
+        return 1.0;
+    }
+    
+    let compared = cmp::min(seq1.len(), seq2.len());
+    let mut score = 0.0;
+    let mut i = 0;
+    
+    while i < compared {
+        if options.words_equal(seq1[i], seq2[i]) {
+            score += 1.0;
+            i += 1;
+        } else if i + 1 < compared && options.words_equal(seq1[i], seq2[i + 1]) && options.words_equal(seq1[i + 1], seq2[i]) {
+            // Look one ahead: both positions of the swapped pair earn partial credit
+            score += 2.0 * ADJACENT_SWAP_CREDIT;
+            i += 2;
+        } else {
+            i += 1;
+        }
+    }
+    
+    score / cmp::max(seq1.len(), seq2.len()) as f64
+}
+
+/// Score a window against the search words using the configured comparison
+fn window_similarity(window: &[&str], search_words: &[&str], options: &MatchOptions) -> f64 {
+    let similarity = match options.metric {
+        SimilarityMetric::Positional if options.allow_adjacent_swaps => swap_tolerant_similarity(window, search_words, options),
+        SimilarityMetric::Positional => positional_similarity(window, search_words, options),
+        SimilarityMetric::WordLcs => lcs_similarity_by(window, search_words, |a, b| options.words_equal(a, b)),
+        SimilarityMetric::LengthWeighted => length_weighted_similarity_by(window, search_words, |a, b| options.words_equal(a, b)),
+    };
+    let similarity = if options.aggregation == Aggregation::Mean {
+        similarity
+    } else {
+        similarity.min(fuzzy_sequence_similarity(window, search_words, options))
+    };
+    
+    if options.length_penalty {
+        similarity * length_match_factor(window, search_words)
+    } else {
+        similarity
+    }
+}
+
+/// Character similarity of the concatenated words, the fuzzy pass's alternative to
+/// word-by-word scoring; 0 under `whole_word_only`, which allows no partial credit,
+/// and under a non-`Mean` aggregation, which it would otherwise override
+fn char_overlap_similarity(window: &[&str], search_words: &[&str], options: &MatchOptions) -> f64 {
+    if options.whole_word_only || options.aggregation != Aggregation::Mean {
+        return 0.0;
+    }
+    
+    calculate_text_similarity(&window.join(""), &search_words.join(""))
+}
+
+/// `1 - |len_diff| / max_len` over the characters of both word sequences (spaces included)
+fn length_match_factor(window: &[&str], search_words: &[&str]) -> f64 {
+    let window_len = window.join(" ").chars().count();
+    let search_len = search_words.join(" ").chars().count();
+    let max_len = cmp::max(window_len, search_len);
+    if max_len == 0 {
+        return 1.0;
+    }
+    
+    1.0 - window_len.abs_diff(search_len) as f64 / max_len as f64
+}
+
 /// Calculate fuzzy similarity between two words
-fn calculate_word_similarity(word1: &str, word2: &str) -> f64 {
-    if word1 == word2 {
+fn calculate_word_similarity(word1: &str, word2: &str, options: &MatchOptions) -> f64 {
+    if options.words_equal(word1, word2) {
         return 1.0;
     }
+    if options.whole_word_only {
+        return 0.0;
+    }
     if word1.contains(word2) || word2.contains(word1) {
-        return 0.8;
+        let (longer, shorter) = if word1.len() >= word2.len() { (word1, word2) } else { (word2, word1) };
+        if options.affix_overlap_credit && (longer.starts_with(shorter) || longer.ends_with(shorter)) {
+            return grapheme_clusters(shorter).len() as f64 / grapheme_clusters(longer).len() as f64;
+        }
+        return options.substring_credit;
     }
     
     // Simple character-based similarity
-    let chars1: Vec<char> = word1.chars().collect();
-    let chars2: Vec<char> = word2.chars().collect();
+    let chars1 = grapheme_clusters(word1);
+    let chars2 = grapheme_clusters(word2);
     let matching = chars1.iter().filter(|c| chars2.contains(c)).count();
     matching as f64 / cmp::max(chars1.len(), chars2.len()) as f64
 }
 
+/// User-perceived characters: the extended grapheme clusters (UAX #29) of the NFC form,
+/// so "é" and "e\u{301}" are the same single cluster and emoji sequences stay whole
+pub(crate) fn grapheme_clusters(text: &str) -> Vec<String> {
+    let composed: String = text.nfc().collect();
+    composed.graphemes(true).map(str::to_string).collect()
+}
+
 /// Enhanced sequence similarity with fuzzy word matching
-fn fuzzy_sequence_similarity(seq1: &[&str], seq2: &[&str]) -> f64 {
+fn fuzzy_sequence_similarity(seq1: &[&str], seq2: &[&str], options: &MatchOptions) -> f64 {
     if seq1.is_empty() && seq2.is_empty() {
         return 1.0;
     }
@@ -76,16 +486,54 @@ fn fuzzy_sequence_similarity(seq1: &[&str], seq2: &[&str]) -> f64 {
         return 0.0;
     }
     
-    let mut total_similarity = 0.0;
     let max_len = cmp::max(seq1.len(), seq2.len());
+    let word_similarities: Vec<f64> = (0..max_len)
+        .map(|i| {
+            if i < seq1.len() && i < seq2.len() {
+                calculate_word_similarity(seq1[i], seq2[i], options)
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    
+    aggregate_similarities(&word_similarities, options.aggregation)
+}
+
+/// Combine per-word similarities (non-empty) into one score
+fn aggregate_similarities(similarities: &[f64], aggregation: Aggregation) -> f64 {
+    let count = similarities.len() as f64;
+    match aggregation {
+        Aggregation::Mean => similarities.iter().sum::<f64>() / count,
+        Aggregation::Min => similarities.iter().copied().fold(f64::INFINITY, f64::min),
+        Aggregation::Harmonic => {
+            if similarities.iter().any(|&s| s <= 0.0) {
+                return 0.0;
+            }
+            count / similarities.iter().map(|s| 1.0 / s).sum::<f64>()
+        }
+    }
+}
+
+/// Fuzzy similarity of a window that may contain extra interspersed words
+/// Search words are aligned in order to the best-scoring window words (skipping the
+/// rest) and each skipped window word costs a small penalty
+fn gapped_sequence_similarity(window: &[&str], search_words: &[&str], options: &MatchOptions) -> f64 {
+    if search_words.is_empty() {
+        return if window.is_empty() { 1.0 } else { 0.0 };
+    }
     
-    for i in 0..max_len {
-        if i < seq1.len() && i < seq2.len() {
-            total_similarity += calculate_word_similarity(seq1[i], seq2[i]);
+    // best[i][j]: highest total word similarity aligning window[..i] with search_words[..j]
+    let mut best = vec![vec![0.0; search_words.len() + 1]; window.len() + 1];
+    for i in 1..=window.len() {
+        for j in 1..=search_words.len() {
+            let aligned = best[i - 1][j - 1] + calculate_word_similarity(window[i - 1], search_words[j - 1], options);
+            best[i][j] = aligned.max(best[i - 1][j]).max(best[i][j - 1]);
         }
     }
     
-    total_similarity / max_len as f64
+    let extra_words = window.len().saturating_sub(search_words.len()) as f64;
+    best[window.len()][search_words.len()] / (search_words.len() as f64 + EXTRA_WORD_PENALTY * extra_words)
 }
 
 /// Calculate text similarity between two text strings (character-based)
@@ -94,8 +542,10 @@ fn calculate_text_similarity(text1: &str, text2: &str) -> f64 {
         return 1.0;
     }
     
-    let len1 = text1.len();
-    let len2 = text2.len();
+    let chars1 = grapheme_clusters(text1);
+    let chars2 = grapheme_clusters(text2);
+    let len1 = chars1.len();
+    let len2 = chars2.len();
     let max_len = cmp::max(len1, len2);
     
     if max_len == 0 {
@@ -105,8 +555,6 @@ fn calculate_text_similarity(text1: &str, text2: &str) -> f64 {
     // Calculate character overlap
     let mut matching = 0;
     let min_len = cmp::min(len1, len2);
-    let chars1: Vec<char> = text1.chars().collect();
-    let chars2: Vec<char> = text2.chars().collect();
     
     for i in 0..min_len {
         if chars1[i] == chars2[i] {
@@ -117,10 +565,109 @@ fn calculate_text_similarity(text1: &str, text2: &str) -> f64 {
     matching as f64 / max_len as f64
 }
 
+/// Normalized similarity (0.0 - 1.0) between two arbitrary strings
+/// `metric` is `"levenshtein"`, `"jaro_winkler"` or `"trigram"` (case-insensitive,
+/// `-` accepted for `_`); None for an unknown metric
+#[wasm_bindgen]
+pub fn string_similarity(a: &str, b: &str, metric: &str) -> Option<f64> {
+    match metric.to_lowercase().replace('-', "_").as_str() {
+        "levenshtein" => Some(levenshtein_similarity(a, b)),
+        "jaro_winkler" | "jarowinkler" => Some(jaro_winkler_similarity(a, b)),
+        "trigram" => Some(trigram_similarity(a, b)),
+        _ => None,
+    }
+}
+
+/// `1 - edit distance / longer length`, over chars
+fn levenshtein_similarity(a: &str, b: &str) -> f64 {
+    let chars1: Vec<char> = a.chars().collect();
+    let chars2: Vec<char> = b.chars().collect();
+    let max_len = cmp::max(chars1.len(), chars2.len());
+    if max_len == 0 {
+        return 1.0;
+    }
+    
+    let mut previous: Vec<usize> = (0..=chars2.len()).collect();
+    let mut current = vec![0; chars2.len() + 1];
+    for (i, c1) in chars1.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, c2) in chars2.iter().enumerate() {
+            let substitution = previous[j] + usize::from(c1 != c2);
+            current[j + 1] = cmp::min(substitution, cmp::min(previous[j + 1], current[j]) + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    
+    1.0 - previous[chars2.len()] as f64 / max_len as f64
+}
+
+/// Jaro similarity boosted by up to four chars of common prefix (scaling 0.1)
+fn jaro_winkler_similarity(a: &str, b: &str) -> f64 {
+    let chars1: Vec<char> = a.chars().collect();
+    let chars2: Vec<char> = b.chars().collect();
+    if chars1.is_empty() && chars2.is_empty() {
+        return 1.0;
+    }
+    if chars1.is_empty() || chars2.is_empty() {
+        return 0.0;
+    }
+    
+    let match_range = (cmp::max(chars1.len(), chars2.len()) / 2).saturating_sub(1);
+    let mut matched1 = vec![false; chars1.len()];
+    let mut matched2 = vec![false; chars2.len()];
+    let mut matches = 0;
+    
+    for (i, c1) in chars1.iter().enumerate() {
+        let low = i.saturating_sub(match_range);
+        let high = cmp::min(i + match_range + 1, chars2.len());
+        for j in low..high {
+            if !matched2[j] && chars2[j] == *c1 {
+                matched1[i] = true;
+                matched2[j] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+    if matches == 0 {
+        return 0.0;
+    }
+    
+    let in_order1 = chars1.iter().zip(&matched1).filter(|(_, m)| **m).map(|(c, _)| c);
+    let in_order2 = chars2.iter().zip(&matched2).filter(|(_, m)| **m).map(|(c, _)| c);
+    let transpositions = in_order1.zip(in_order2).filter(|(c1, c2)| c1 != c2).count() / 2;
+    
+    let m = matches as f64;
+    let jaro = (m / chars1.len() as f64 + m / chars2.len() as f64 + (m - transpositions as f64) / m) / 3.0;
+    let prefix = chars1.iter().zip(&chars2).take(4).take_while(|(c1, c2)| c1 == c2).count();
+    
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+/// Jaccard overlap of the space-padded character trigram sets
+fn trigram_similarity(a: &str, b: &str) -> f64 {
+    fn trigrams(text: &str) -> std::collections::HashSet<[char; 3]> {
+        let padded: Vec<char> = format!(" {} ", text).chars().collect();
+        padded.windows(3).map(|w| [w[0], w[1], w[2]]).collect()
+    }
+    
+    if a == b {
+        return 1.0;
+    }
+    let (grams1, grams2) = (trigrams(a), trigrams(b));
+    let union = grams1.union(&grams2).count();
+    if union == 0 {
+        return 0.0;
+    }
+    
+    grams1.intersection(&grams2).count() as f64 / union as f64
+}
+
 /// Find fuzzy matches using enhanced similarity algorithms
 fn find_fuzzy_match(
     cleaned_words: &[&str], 
     search_words: &[&str], 
+    options: &MatchOptions,
     debug_cleaned_text: &str,
     debug_search_words: &str,
     debug_cleaned_word_count: usize,
@@ -128,27 +675,42 @@ fn find_fuzzy_match(
 ) -> Option<MatchResult> {
     let mut best_match: Option<MatchResult> = None;
     let mut best_similarity = 0.0;
+    // Tiebreak key among equal scores: distance from the search length, then start index
+    let mut best_key = (usize::MAX, usize::MAX);
     
     // Try different window sizes around the expected length
     let min_window = cmp::max(1, search_words.len().saturating_sub(2));
-    let max_window = cmp::min(cleaned_words.len(), search_words.len() + 3);
+    let extra_words = options.max_extra_words.unwrap_or(DEFAULT_MAX_EXTRA_WORDS);
+    let max_window = cmp::min(cleaned_words.len(), search_words.len() + extra_words);
     
     for window_size in min_window..=max_window {
         for i in 0..=cleaned_words.len().saturating_sub(window_size) {
+            if !options.window_allowed(cleaned_words, i, window_size) {
+                continue;
+            }
             let window = &cleaned_words[i..i + window_size];
             
             // Try both fuzzy word matching and character-based matching
-            let fuzzy_similarity = fuzzy_sequence_similarity(window, search_words);
-            let char_similarity = calculate_text_similarity(&window.join(""), &search_words.join(""));
+            let fuzzy_similarity = if options.max_extra_words.is_some() && window_size > search_words.len() {
+                gapped_sequence_similarity(window, search_words, options)
+            } else {
+                fuzzy_sequence_similarity(window, search_words, options)
+            };
+            let char_similarity = char_overlap_similarity(window, search_words, options);
+            let length_factor = if options.length_penalty { length_match_factor(window, search_words) } else { 1.0 };
             
             // Use the better of the two similarity scores
             let similarity = cmp::max(
-                (fuzzy_similarity * 1000.0) as i32,
-                (char_similarity * 1000.0) as i32
+                (fuzzy_similarity * length_factor * 1000.0) as i32,
+                (char_similarity * length_factor * 1000.0) as i32
             ) as f64 / 1000.0;
             
-            if similarity > best_similarity && similarity > 0.6 {
+            let key = (window_size.abs_diff(search_words.len()), i);
+            let better = similarity > best_similarity || (similarity == best_similarity && key < best_key);
+            
+            if better && similarity > options.fuzzy_accept_threshold {
                 best_similarity = similarity;
+                best_key = key;
                 let matched_text = window.join(" ");
                 
                 best_match = Some(MatchResult {
@@ -160,6 +722,9 @@ fn find_fuzzy_match(
                     debug_search_words: debug_search_words.to_string(),
                     debug_cleaned_word_count,
                     debug_search_word_count,
+                    window_size,
+                    unmatched_search_words: unmatched_search_word_indices(window, search_words, options),
+                    weakest_word: weakest_aligned_word(window, search_words, options),
                 });
             }
         }
@@ -168,6 +733,52 @@ fn find_fuzzy_match(
     best_match
 }
 
+/// Indices of search words whose best similarity to any word in the window falls
+/// below `UNMATCHED_WORD_THRESHOLD`
+fn unmatched_search_word_indices(window: &[&str], search_words: &[&str], options: &MatchOptions) -> Vec<usize> {
+    search_words.iter()
+        .enumerate()
+        .filter(|(_, search_word)| {
+            let best = window.iter()
+                .map(|word| calculate_word_similarity(word, search_word, options))
+                .fold(0.0, f64::max);
+            best < UNMATCHED_WORD_THRESHOLD
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Lowest-scoring pair when window and search words are aligned by position
+/// (the first such pair on ties); None when either side is empty
+fn weakest_aligned_word(window: &[&str], search_words: &[&str], options: &MatchOptions) -> Option<WeakestWord> {
+    window.iter()
+        .zip(search_words)
+        .enumerate()
+        .map(|(position, (word, search_word))| WeakestWord {
+            word: word.to_string(),
+            position,
+            similarity: calculate_word_similarity(word, search_word, options),
+        })
+        .reduce(|weakest, pair| if pair.similarity < weakest.similarity { pair } else { weakest })
+}
+
+/// Rewrite embedded text markers in canonical form: `[[ line\t1  2 3 4 ]]` becomes
+/// `[[LINE 1 2 3 4]]` followed by exactly one space
+#[wasm_bindgen]
+pub fn normalize_markers(embedded_text: &str) -> String {
+    let marker_regex = regex::Regex::new(r"\[\[\s*([A-Za-z]+)([^\]]*?)\s*\]\]\s*").unwrap();
+    
+    marker_regex.replace_all(embedded_text, |caps: &regex::Captures| {
+        let keyword = caps[1].to_uppercase();
+        let arguments: Vec<&str> = caps[2].split_whitespace().collect();
+        if arguments.is_empty() {
+            format!("[[{}]] ", keyword)
+        } else {
+            format!("[[{} {}]] ", keyword, arguments.join(" "))
+        }
+    }).to_string()
+}
+
 /// Clean embedded text by removing hOCR markers
 /// Ported from Python closest_match.py logic
 fn clean_embedded_text(text: &str) -> String {
@@ -182,15 +793,101 @@ fn clean_embedded_text(text: &str) -> String {
 /// Ported from Python closest_match.py:find_closest_match()
 #[wasm_bindgen]
 pub fn find_closest_match(embedded_text: &str, search_string: &str) -> Option<MatchResult> {
+    find_closest_match_with_options(embedded_text, search_string, &MatchOptions::default())
+}
+
+/// Find the closest match using the given matcher options
+#[wasm_bindgen]
+pub fn find_closest_match_with_options(
+    embedded_text: &str,
+    search_string: &str,
+    options: &MatchOptions,
+) -> Option<MatchResult> {
     // Add safety checks
     if embedded_text.is_empty() || search_string.is_empty() {
         return None;
     }
     
+    if options.normalize_punctuation_variants {
+        let plain = MatchOptions { normalize_punctuation_variants: false, ..options.clone() };
+        return find_closest_match_with_options(
+            &normalize_punctuation(embedded_text),
+            &normalize_punctuation(search_string),
+            &plain,
+        );
+    }
+    
+    if !options.respect_line_boundaries {
+        let strict = MatchOptions { respect_line_boundaries: true, ..options.clone() };
+        let best_match = find_closest_match_with_options(embedded_text, search_string, &strict);
+        
+        // Rematch with each line's first word joined onto the previous line's last word
+        let (cleaned_words, joined_words, spans) = join_across_line_breaks(embedded_text);
+        let joined = match_merged_words(&cleaned_words, &joined_words, &spans, search_string, &strict);
+        
+        return better_match(best_match, joined);
+    }
+    
+    if options.rejoin_split_words {
+        let strict = MatchOptions { rejoin_split_words: false, ..options.clone() };
+        let best_match = find_closest_match_with_options(embedded_text, search_string, &strict);
+        
+        let cleaned_text = clean_embedded_text(&normalize_markers(embedded_text));
+        let cleaned_words: Vec<String> = cleaned_text.split_whitespace().map(str::to_string).collect();
+        let (rejoined_words, spans) = rejoin_split_search_words(&cleaned_words, search_string, options);
+        let rejoined = match_merged_words(&cleaned_words, &rejoined_words, &spans, search_string, &strict);
+        
+        return better_match(best_match, rejoined);
+    }
+    
+    if options.flexible_hyphens && search_string.contains('-') {
+        let strict = MatchOptions { flexible_hyphens: false, ..options.clone() };
+        let mut best_match: Option<MatchResult> = None;
+        
+        // The original spelling comes first, so it wins ties
+        for variant in hyphen_variants(search_string) {
+            if let Some(candidate) = find_closest_match_with_options(embedded_text, &variant, &strict) {
+                if best_match.as_ref().is_none_or(|best| candidate.similarity > best.similarity) {
+                    best_match = Some(candidate);
+                }
+            }
+        }
+        
+        return best_match;
+    }
+    
     // Clean the embedded text by removing hOCR markers
-    let cleaned_text = clean_embedded_text(embedded_text);
-    let cleaned_words: Vec<&str> = cleaned_text.split_whitespace().collect();
-    let search_words: Vec<&str> = search_string.split_whitespace().collect();
+    let cleaned_text = clean_embedded_text(&normalize_markers(embedded_text));
+    let all_words: Vec<&str> = cleaned_text.split_whitespace().collect();
+    let grouped_search: Vec<String>;
+    let mut search_words: Vec<&str> = search_string.split_whitespace().collect();
+    
+    // Words taking part in matching, as `[start, end)` ranges of `all_words`; a range
+    // only covers several words when a space-grouped number is joined
+    let spans: Vec<(usize, usize)> = if options.normalize_number_grouping {
+        number_group_spans(&all_words)
+    } else {
+        (0..all_words.len()).map(|i| (i, i + 1)).collect()
+    };
+    let grouped_words: Vec<String>;
+    let mut cleaned_words: Vec<&str> = spans.iter().map(|&(start, _)| all_words[start]).collect();
+    if options.normalize_number_grouping {
+        grouped_words = spans.iter().map(|&(start, end)| strip_number_grouping(&all_words[start..end].concat())).collect();
+        cleaned_words = grouped_words.iter().map(String::as_str).collect();
+        grouped_search = number_group_spans(&search_words)
+            .into_iter()
+            .map(|(start, end)| strip_number_grouping(&search_words[start..end].concat()))
+            .collect();
+        search_words = grouped_search.iter().map(String::as_str).collect();
+    }
+    
+    let (spans, cleaned_words): (Vec<(usize, usize)>, Vec<&str>) = spans.into_iter()
+        .zip(cleaned_words)
+        .filter(|&(_, word)| !(options.drop_numeric_tokens && is_numeric_token(word)))
+        .unzip();
+    if options.drop_numeric_tokens {
+        search_words.retain(|w| !is_numeric_token(w));
+    }
     
     // Prepare debug information
     let debug_cleaned_text = cleaned_text.chars().take(500).collect::<String>(); // First 500 chars
@@ -202,9 +899,10 @@ pub fn find_closest_match(embedded_text: &str, search_string: &str) -> Option<Ma
         return None;
     }
     
-    // Additional safety check for window size
+    // No window fits: as a last resort, score the entire text against the search
     if search_words.len() > cleaned_words.len() {
-        return None;
+        let whole_text = whole_text_match(&cleaned_words, &search_words, options, &debug_cleaned_text, &debug_search_words);
+        return report_against_all_words(whole_text, &spans, &all_words, options);
     }
     
     let window_size = search_words.len();
@@ -213,8 +911,11 @@ pub fn find_closest_match(embedded_text: &str, search_string: &str) -> Option<Ma
     
     // Sliding window approach with exact matching first
     for i in 0..=cleaned_words.len().saturating_sub(window_size) {
+        if !options.window_allowed(&cleaned_words, i, window_size) {
+            continue;
+        }
         let window = &cleaned_words[i..i + window_size];
-        let similarity = sequence_similarity(window, &search_words);
+        let similarity = window_similarity(window, &search_words, options);
         
         if similarity > best_similarity {
             best_similarity = similarity;
@@ -229,6 +930,9 @@ pub fn find_closest_match(embedded_text: &str, search_string: &str) -> Option<Ma
                 debug_search_words: debug_search_words.clone(),
                 debug_cleaned_word_count,
                 debug_search_word_count,
+                window_size,
+                unmatched_search_words: unmatched_search_word_indices(window, &search_words, options),
+                weakest_word: None,
             });
             
             // Early exit for high similarity matches (performance optimization)
@@ -240,71 +944,1112 @@ pub fn find_closest_match(embedded_text: &str, search_string: &str) -> Option<Ma
     
     // If exact matching didn't find a good match, try fuzzy matching
     if best_similarity < 0.8 {
-        if let Some(fuzzy_match) = find_fuzzy_match(&cleaned_words, &search_words, &debug_cleaned_text, &debug_search_words, debug_cleaned_word_count, debug_search_word_count) {
+        if let Some(fuzzy_match) = find_fuzzy_match(&cleaned_words, &search_words, options, &debug_cleaned_text, &debug_search_words, debug_cleaned_word_count, debug_search_word_count) {
             if fuzzy_match.similarity > best_similarity {
                 best_match = Some(fuzzy_match);
             }
         }
     }
     
-    best_match
+    report_against_all_words(best_match, &spans, &all_words, options)
 }
 
-/// Find multiple matches for a list of search strings
-/// Returns all matches above the similarity threshold
-#[wasm_bindgen]
-pub fn find_multiple_matches(
-    embedded_text: &str, 
-    search_strings: &js_sys::Array,
-    similarity_threshold: f64
-) -> js_sys::Array {
-    let results = js_sys::Array::new();
-    
-    for i in 0..search_strings.length() {
-        if let Some(search_str) = search_strings.get(i).as_string() {
-            if let Some(match_result) = find_closest_match(embedded_text, &search_str) {
-                if match_result.similarity >= similarity_threshold {
-                    let js_result = js_sys::Object::new();
-                    js_sys::Reflect::set(&js_result, &"text".into(), &match_result.text.into()).unwrap();
-                    js_sys::Reflect::set(&js_result, &"similarity".into(), &match_result.similarity.into()).unwrap();
-                    js_sys::Reflect::set(&js_result, &"startIndex".into(), &(match_result.start_index as u32).into()).unwrap();
-                    js_sys::Reflect::set(&js_result, &"endIndex".into(), &(match_result.end_index as u32).into()).unwrap();
-                    js_sys::Reflect::set(&js_result, &"searchQuery".into(), &search_str.into()).unwrap();
-                    
-                    results.push(&js_result);
-                }
-            }
+/// Report a result's indices (and text) against the unfiltered, ungrouped words
+fn report_against_all_words(
+    mut best_match: Option<MatchResult>,
+    spans: &[(usize, usize)],
+    all_words: &[&str],
+    options: &MatchOptions,
+) -> Option<MatchResult> {
+    if options.drop_numeric_tokens || options.normalize_number_grouping {
+        if let Some(result) = best_match.as_mut() {
+            result.start_index = spans[result.start_index].0;
+            result.end_index = spans[result.end_index - 1].1;
+            result.window_size = result.end_index - result.start_index;
+            result.text = all_words[result.start_index..result.end_index].join(" ");
         }
     }
     
-    results
+    best_match
+}
+
+/// Fallback for a search longer than the text: the whole text scored like a fuzzy-pass
+/// window, kept only above `fuzzy_accept_threshold`
+fn whole_text_match(
+    cleaned_words: &[&str],
+    search_words: &[&str],
+    options: &MatchOptions,
+    debug_cleaned_text: &str,
+    debug_search_words: &str,
+) -> Option<MatchResult> {
+    if !options.window_allowed(cleaned_words, 0, cleaned_words.len()) {
+        return None;
+    }
+    let fuzzy_similarity = fuzzy_sequence_similarity(cleaned_words, search_words, options);
+    let char_similarity = char_overlap_similarity(cleaned_words, search_words, options);
+    let similarity = cmp::max(
+        (fuzzy_similarity * 1000.0) as i32,
+        (char_similarity * 1000.0) as i32
+    ) as f64 / 1000.0;
+    
+    if similarity <= options.fuzzy_accept_threshold {
+        return None;
+    }
+    
+    Some(MatchResult {
+        text: cleaned_words.join(" "),
+        similarity,
+        start_index: 0,
+        end_index: cleaned_words.len(),
+        debug_cleaned_text: debug_cleaned_text.to_string(),
+        debug_search_words: debug_search_words.to_string(),
+        debug_cleaned_word_count: cleaned_words.len(),
+        debug_search_word_count: search_words.len(),
+        window_size: cleaned_words.len(),
+        unmatched_search_words: unmatched_search_word_indices(cleaned_words, search_words, options),
+        weakest_word: weakest_aligned_word(cleaned_words, search_words, options),
+    })
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Match against `merged_words`, each standing for the `spans` range of `cleaned_words`,
+/// and report the result's indices and text against the cleaned words
+fn match_merged_words(
+    cleaned_words: &[String],
+    merged_words: &[String],
+    spans: &[(usize, usize)],
+    search_string: &str,
+    options: &MatchOptions,
+) -> Option<MatchResult> {
+    find_closest_match_with_options(&merged_words.join(" "), search_string, options)
+        .map(|mut result| {
+            result.start_index = spans[result.start_index].0;
+            result.end_index = spans[result.end_index - 1].1;
+            result.window_size = result.end_index - result.start_index;
+            result.text = cleaned_words[result.start_index..result.end_index].join(" ");
+            result
+        })
+}
 
-    #[test]
-    fn test_sequence_similarity() {
-        let seq1 = vec!["hello", "world"];
-        let seq2 = vec!["hello", "world"];
-        assert_eq!(sequence_similarity(&seq1, &seq2), 1.0);
-        
-        let seq1 = vec!["hello", "world"];
-        let seq2 = vec!["hello", "universe"];
-        assert_eq!(sequence_similarity(&seq1, &seq2), 0.5);
+/// The higher-scoring of two results; the first wins ties
+fn better_match(first: Option<MatchResult>, second: Option<MatchResult>) -> Option<MatchResult> {
+    match (first, second) {
+        (Some(first), Some(second)) if second.similarity > first.similarity => Some(second),
+        (None, second) => second,
+        (first, _) => first,
     }
+}
 
-    #[test]
-    fn test_find_closest_match() {
-        let embedded_text = "[[PARAGRAPH]] [[LINE 100 200 300 400]] hello world test [[LINE 500 600 700 800]] another line";
-        let search_string = "hello world";
-        
-        let result = find_closest_match(embedded_text, search_string);
+/// Cleaned words with each adjacent pair whose concatenation equals a search word
+/// (and whose first half does not) merged into one, plus the `[start, end)` range of
+/// cleaned words behind each result word
+/// Equality is the matcher's own `words_equal`, so a pair is only merged when the merged
+/// word will match
+fn rejoin_split_search_words(
+    cleaned_words: &[String],
+    search_string: &str,
+    options: &MatchOptions,
+) -> (Vec<String>, Vec<(usize, usize)>) {
+    let search_words: Vec<&str> = search_string.split_whitespace().collect();
+    let is_search_word = |word: &str| search_words.iter().any(|search_word| options.words_equal(word, search_word));
+    
+    let mut words = Vec::with_capacity(cleaned_words.len());
+    let mut spans = Vec::with_capacity(cleaned_words.len());
+    let mut i = 0;
+    while i < cleaned_words.len() {
+        if let Some(next) = cleaned_words.get(i + 1) {
+            let concatenated = format!("{}{}", cleaned_words[i], next);
+            if !is_search_word(&cleaned_words[i]) && is_search_word(&concatenated) {
+                words.push(concatenated);
+                spans.push((i, i + 2));
+                i += 2;
+                continue;
+            }
+        }
+        words.push(cleaned_words[i].clone());
+        spans.push((i, i + 1));
+        i += 1;
+    }
+    
+    (words, spans)
+}
+
+/// The cleaned words of embedded text, the same words with each line's first word
+/// joined onto the previous line's last word (dropping a trailing hyphen), and the
+/// `[start, end)` range of cleaned words behind each joined word
+fn join_across_line_breaks(embedded_text: &str) -> (Vec<String>, Vec<String>, Vec<(usize, usize)>) {
+    let normalized = normalize_markers(embedded_text);
+    let marker_regex = regex::Regex::new(r"\[\[.*?\]\] ").unwrap();
+    
+    // Rebuild the cleaned text as `clean_embedded_text` does, noting where lines begin
+    let mut cleaned_text = String::with_capacity(normalized.len());
+    let mut line_starts: Vec<usize> = Vec::new();
+    let mut last_end = 0;
+    for marker in marker_regex.find_iter(&normalized) {
+        cleaned_text.push_str(&normalized[last_end..marker.start()]);
+        if marker.as_str().starts_with("[[LINE") {
+            line_starts.push(cleaned_text.len());
+        }
+        last_end = marker.end();
+    }
+    cleaned_text.push_str(&normalized[last_end..]);
+    
+    let tokens: Vec<(usize, &str)> = regex::Regex::new(r"\S+").unwrap()
+        .find_iter(&cleaned_text)
+        .map(|token| (token.start(), token.as_str()))
+        .collect();
+    
+    let mut joined_words: Vec<String> = Vec::with_capacity(tokens.len());
+    let mut spans: Vec<(usize, usize)> = Vec::with_capacity(tokens.len());
+    for (i, &(start, token)) in tokens.iter().enumerate() {
+        let starts_line = line_starts.contains(&start);
+        match (joined_words.last_mut(), spans.last_mut()) {
+            (Some(previous), Some(span)) if starts_line && span.1 - span.0 == 1 => {
+                *previous = format!("{}{}", previous.trim_end_matches('-'), token);
+                span.1 = i + 1;
+            }
+            _ => {
+                joined_words.push(token.to_string());
+                spans.push((i, i + 1));
+            }
+        }
+    }
+    
+    let cleaned_words = tokens.into_iter().map(|(_, token)| token.to_string()).collect();
+    (cleaned_words, joined_words, spans)
+}
+
+/// Map typographic quote and dash variants to their ASCII forms
+fn normalize_punctuation(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' => '\'',
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' => '"',
+            '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2015}' | '\u{2212}' => '-',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Ranges of `words` to read as one word: a 1-3 digit word followed by 3-digit words
+/// (a number grouped with spaces) is one range, every other word its own
+fn number_group_spans(words: &[&str]) -> Vec<(usize, usize)> {
+    let is_group = |word: &str| word.len() == 3 && is_numeric_token(word);
+    let mut spans = Vec::with_capacity(words.len());
+    let mut start = 0;
+    
+    while start < words.len() {
+        let mut end = start + 1;
+        if is_numeric_token(words[start]) && words[start].len() <= 3 {
+            while end < words.len() && is_group(words[end]) {
+                end += 1;
+            }
+        }
+        spans.push((start, end));
+        start = end;
+    }
+    
+    spans
+}
+
+/// Drop the thousands separators from a comma- or apostrophe-grouped number such as
+/// "1,000,000"; any other word is returned unchanged
+fn strip_number_grouping(word: &str) -> String {
+    let groups: Vec<&str> = word.split([',', '\'', '\u{2009}', '\u{202F}']).collect();
+    let grouped = groups.len() > 1
+        && groups.iter().all(|group| is_numeric_token(group))
+        && groups[0].len() <= 3
+        && groups[1..].iter().all(|group| group.len() == 3);
+    
+    if grouped {
+        groups.concat()
+    } else {
+        word.to_string()
+    }
+}
+
+/// Token made only of ASCII digits, e.g. a page number
+fn is_numeric_token(word: &str) -> bool {
+    !word.is_empty() && word.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Spellings of a search string with each hyphenated word kept, joined or split,
+/// starting with the original; capped at `MAX_HYPHEN_VARIANTS`
+fn hyphen_variants(search_string: &str) -> Vec<String> {
+    let mut variants: Vec<Vec<String>> = vec![Vec::new()];
+    
+    for word in search_string.split_whitespace() {
+        let parts: Vec<&str> = word.split('-').filter(|part| !part.is_empty()).collect();
+        let spellings = if parts.len() > 1 && variants.len() * 3 <= MAX_HYPHEN_VARIANTS {
+            vec![word.to_string(), parts.concat(), parts.join(" ")]
+        } else {
+            vec![word.to_string()]
+        };
+        
+        variants = variants
+            .into_iter()
+            .flat_map(|prefix| {
+                spellings.iter().map(move |spelling| {
+                    let mut words = prefix.clone();
+                    words.push(spelling.clone());
+                    words
+                })
+            })
+            .collect();
+    }
+    
+    variants.into_iter().map(|words| words.join(" ")).collect()
+}
+
+/// Match each search string and keep the results above the similarity threshold
+/// Results are in query order, or by start index when `sort_by_position` is set
+/// (ties keep query order)
+fn collect_multiple_matches(
+    embedded_text: &str,
+    search_strings: &[String],
+    similarity_threshold: f64,
+    sort_by_position: bool,
+) -> Vec<(String, MatchResult)> {
+    let mut matches: Vec<(String, MatchResult)> = search_strings.iter()
+        .filter_map(|search_str| {
+            find_closest_match(embedded_text, search_str)
+                .filter(|m| m.similarity >= similarity_threshold)
+                .map(|m| (search_str.clone(), m))
+        })
+        .collect();
+    
+    if sort_by_position {
+        // Stable sort, so equal start indices stay in query order
+        matches.sort_by_key(|(_, m)| m.start_index);
+    }
+    
+    matches
+}
+
+/// One item of `find_multiple_matches`, serialized to JS with camelCase keys
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultipleMatchEntry {
+    pub text: String,
+    pub similarity: f64,
+    pub start_index: usize,
+    pub end_index: usize,
+    pub search_query: String,
+}
+
+impl MultipleMatchEntry {
+    fn new(search_query: String, match_result: MatchResult) -> MultipleMatchEntry {
+        MultipleMatchEntry {
+            text: match_result.text,
+            similarity: match_result.similarity,
+            start_index: match_result.start_index,
+            end_index: match_result.end_index,
+            search_query,
+        }
+    }
+}
+
+/// Label a similarity score as "high", "medium" or "low" confidence
+/// Defaults: high at 0.9 and above, medium at 0.7 and above
+#[wasm_bindgen]
+pub fn classify_match_confidence(
+    similarity: f64,
+    high_threshold: Option<f64>,
+    medium_threshold: Option<f64>,
+) -> String {
+    let high = high_threshold.unwrap_or(0.9);
+    let medium = medium_threshold.unwrap_or(0.7);
+    
+    if similarity >= high {
+        "high".to_string()
+    } else if similarity >= medium {
+        "medium".to_string()
+    } else {
+        "low".to_string()
+    }
+}
+
+/// Plain-text preview of the closest match with up to `context_words` words either side,
+/// e.g. `"... saw the [quick brown fox] jump over ..."`; ellipses mark elided text
+#[wasm_bindgen]
+pub fn preview_match(embedded_text: &str, search_string: &str, context_words: usize) -> Option<String> {
+    let result = find_closest_match(embedded_text, search_string)?;
+    let cleaned_text = clean_embedded_text(&normalize_markers(embedded_text));
+    let words: Vec<&str> = cleaned_text.split_whitespace().collect();
+    
+    let context_start = result.start_index.saturating_sub(context_words);
+    let context_end = cmp::min(words.len(), result.end_index + context_words);
+    
+    let mut parts: Vec<String> = Vec::new();
+    if context_start > 0 {
+        parts.push("...".to_string());
+    }
+    parts.extend(words[context_start..result.start_index].iter().map(|w| w.to_string()));
+    parts.push(format!("[{}]", words[result.start_index..result.end_index].join(" ")));
+    parts.extend(words[result.end_index..context_end].iter().map(|w| w.to_string()));
+    if context_end < words.len() {
+        parts.push("...".to_string());
+    }
+    
+    Some(parts.join(" "))
+}
+
+/// Find the closest match treating user-supplied aliases as exact matches
+/// `aliases` maps each word to an array of accepted alternatives,
+/// e.g. `{ "St": ["Street"], "Corp": ["Corporation"] }`
+#[wasm_bindgen]
+pub fn find_closest_match_aliased(
+    embedded_text: &str,
+    search_string: &str,
+    aliases: &js_sys::Object,
+) -> Option<MatchResult> {
+    let mut options = MatchOptions::new();
+    
+    for entry in js_sys::Object::entries(aliases).iter() {
+        let entry = js_sys::Array::from(&entry);
+        let Some(word) = entry.get(0).as_string() else {
+            continue;
+        };
+        let alternatives = entry.get(1);
+        if let Some(single) = alternatives.as_string() {
+            options.add_alias(&word, &single);
+        } else if js_sys::Array::is_array(&alternatives) {
+            for alternative in js_sys::Array::from(&alternatives).iter().filter_map(|v| v.as_string()) {
+                options.add_alias(&word, &alternative);
+            }
+        }
+    }
+    
+    find_closest_match_with_options(embedded_text, search_string, &options)
+}
+
+/// Pick non-overlapping windows of `window_size` words from `(start, score)` candidates,
+/// highest score first (earlier start on ties), stopping at `max_results`
+/// The chosen windows are returned in reading order
+pub(crate) fn select_non_overlapping_windows(
+    mut candidates: Vec<(usize, f64)>,
+    window_size: usize,
+    max_results: Option<usize>,
+) -> Vec<(usize, f64)> {
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    
+    let mut chosen: Vec<(usize, f64)> = Vec::new();
+    for (start, similarity) in candidates {
+        if max_results.is_some_and(|max| chosen.len() >= max) {
+            break;
+        }
+        if chosen.iter().all(|&(other, _)| start + window_size <= other || other + window_size <= start) {
+            chosen.push((start, similarity));
+        }
+    }
+    
+    chosen.sort_by_key(|&(start, _)| start);
+    chosen
+}
+
+/// Exact-pass similarity of the window starting at cleaned-word `start_index`, without
+/// searching, e.g. to re-verify a stored match after re-OCR
+/// None when the window would run past the end of the text
+#[wasm_bindgen]
+pub fn similarity_at_index(embedded_text: &str, search_string: &str, start_index: usize) -> Option<f64> {
+    let cleaned_text = clean_embedded_text(&normalize_markers(embedded_text));
+    let cleaned_words: Vec<&str> = cleaned_text.split_whitespace().collect();
+    let search_words: Vec<&str> = search_string.split_whitespace().collect();
+    
+    if search_words.is_empty() {
+        return None;
+    }
+    let window = cleaned_words.get(start_index..start_index.checked_add(search_words.len())?)?;
+    
+    Some(window_similarity(window, &search_words, &MatchOptions::default()))
+}
+
+/// Start of the first window (in reading order) scoring at least `min_similarity`
+/// Stops scanning as soon as one qualifies, so it need not be the best window
+pub(crate) fn first_window_at_or_above(embedded_text: &str, search_string: &str, min_similarity: f64) -> Option<usize> {
+    let cleaned_text = clean_embedded_text(&normalize_markers(embedded_text));
+    let cleaned_words: Vec<&str> = cleaned_text.split_whitespace().collect();
+    let search_words: Vec<&str> = search_string.split_whitespace().collect();
+    
+    let window_size = search_words.len();
+    if window_size == 0 || window_size > cleaned_words.len() {
+        return None;
+    }
+    
+    (0..=cleaned_words.len() - window_size).find(|&i| {
+        let similarity = sequence_similarity(&cleaned_words[i..i + window_size], &search_words);
+        similarity > 0.0 && similarity >= min_similarity
+    })
+}
+
+/// Whether `find_closest_match` would score at least `min_similarity`
+/// An exact-pass window that already qualifies answers early, since the best match
+/// can only score higher; otherwise the full matcher (fuzzy pass included) decides
+#[wasm_bindgen]
+pub fn contains_match(embedded_text: &str, search_string: &str, min_similarity: f64) -> bool {
+    first_window_at_or_above(embedded_text, search_string, min_similarity).is_some()
+        || find_closest_match(embedded_text, search_string).is_some_and(|m| m.similarity >= min_similarity)
+}
+
+/// `find_closest_match` restricted to windows whose surrounding `EXCLUSION_CONTEXT_WORDS`
+/// words on either side contain none of `exclude_terms` (case-insensitive, punctuation
+/// ignored); rejected windows fall through to the next best
+pub fn best_match_excluding(embedded_text: &str, search_string: &str, exclude_terms: &[String]) -> Option<MatchResult> {
+    let options = MatchOptions { exclude_terms: exclude_terms.to_vec(), ..MatchOptions::default() };
+    find_closest_match_with_options(embedded_text, search_string, &options)
+}
+
+/// Words on each side of a window checked for `MatchOptions::exclude_terms`
+const EXCLUSION_CONTEXT_WORDS: usize = 3;
+
+/// Closest match that is not near any of the given exclude terms, e.g. "payment" but
+/// not "payment declined"; non-string array items are ignored
+#[wasm_bindgen]
+pub fn find_match_excluding(embedded_text: &str, search_string: &str, exclude_terms: &js_sys::Array) -> Option<MatchResult> {
+    let exclude_terms: Vec<String> = exclude_terms.iter().filter_map(|v| v.as_string()).collect();
+    best_match_excluding(embedded_text, search_string, &exclude_terms)
+}
+
+/// The candidate phrasing whose closest match scores highest (first wins ties)
+pub fn best_of_candidates(embedded_text: &str, candidates: &[String]) -> Option<(String, MatchResult)> {
+    let mut best: Option<(String, MatchResult)> = None;
+    
+    for candidate in candidates {
+        if let Some(result) = find_closest_match(embedded_text, candidate) {
+            if best.as_ref().is_none_or(|(_, current)| result.similarity > current.similarity) {
+                best = Some((candidate.clone(), result));
+            }
+        }
+    }
+    
+    best
+}
+
+/// Match several phrasings of the same target and keep the best one
+/// Returns `{candidate, text, similarity, startIndex, endIndex}` for the winner
+#[wasm_bindgen]
+pub fn find_best_of_candidates(embedded_text: &str, candidates: &js_sys::Array) -> Option<js_sys::Object> {
+    let candidates: Vec<String> = candidates.iter().filter_map(|v| v.as_string()).collect();
+    let (candidate, match_result) = best_of_candidates(embedded_text, &candidates)?;
+    
+    let js_result = js_sys::Object::new();
+    js_sys::Reflect::set(&js_result, &"candidate".into(), &candidate.into()).unwrap();
+    js_sys::Reflect::set(&js_result, &"text".into(), &match_result.text.into()).unwrap();
+    js_sys::Reflect::set(&js_result, &"similarity".into(), &match_result.similarity.into()).unwrap();
+    js_sys::Reflect::set(&js_result, &"startIndex".into(), &(match_result.start_index as u32).into()).unwrap();
+    js_sys::Reflect::set(&js_result, &"endIndex".into(), &(match_result.end_index as u32).into()).unwrap();
+    
+    Some(js_result)
+}
+
+/// Find multiple matches for a list of search strings
+/// Returns all matches above the similarity threshold
+#[wasm_bindgen]
+pub fn find_multiple_matches(
+    embedded_text: &str, 
+    search_strings: &js_sys::Array,
+    similarity_threshold: f64,
+    sort_by_position: bool,
+) -> js_sys::Array {
+    let queries: Vec<String> = search_strings.iter().filter_map(|v| v.as_string()).collect();
+    let entries: Vec<MultipleMatchEntry> = collect_multiple_matches(embedded_text, &queries, similarity_threshold, sort_by_position)
+        .into_iter()
+        .map(|(search_str, match_result)| MultipleMatchEntry::new(search_str, match_result))
+        .collect();
+    
+    serde_wasm_bindgen::to_value(&entries).unwrap().unchecked_into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence_similarity() {
+        let seq1 = vec!["hello", "world"];
+        let seq2 = vec!["hello", "world"];
+        assert_eq!(sequence_similarity(&seq1, &seq2), 1.0);
+        
+        let seq1 = vec!["hello", "world"];
+        let seq2 = vec!["hello", "universe"];
+        assert_eq!(sequence_similarity(&seq1, &seq2), 0.5);
+    }
+
+    #[test]
+    fn test_find_closest_match() {
+        let embedded_text = "[[PARAGRAPH]] [[LINE 100 200 300 400]] hello world test [[LINE 500 600 700 800]] another line";
+        let search_string = "hello world";
+        
+        let result = find_closest_match(embedded_text, search_string);
         assert!(result.is_some());
         
         let match_result = result.unwrap();
         assert_eq!(match_result.text, "hello world");
         assert_eq!(match_result.similarity, 1.0);
     }
+
+    #[test]
+    fn test_adjacent_swap_option() {
+        let embedded_text = "[[PARAGRAPH]] alpha beta delta gamma epsilon";
+        let search_string = "beta gamma delta epsilon";
+        
+        let strict = find_closest_match(embedded_text, search_string).unwrap();
+        assert!(strict.similarity < 0.7);
+        
+        let mut options = MatchOptions::new();
+        options.allow_adjacent_swaps = true;
+        let tolerant = find_closest_match_with_options(embedded_text, search_string, &options).unwrap();
+        assert_eq!(tolerant.similarity, 0.875);
+        assert_eq!(tolerant.text, "beta delta gamma epsilon");
+    }
+
+    #[test]
+    fn test_multiple_matches_sorted_by_position() {
+        let embedded_text = "[[PARAGRAPH]] [[LINE 0 0 10 10]] first phrase here then second phrase there";
+        let queries = vec!["second phrase".to_string(), "first phrase".to_string()];
+        
+        let in_query_order = collect_multiple_matches(embedded_text, &queries, 0.9, false);
+        assert_eq!(in_query_order[0].0, "second phrase");
+        
+        let by_position = collect_multiple_matches(embedded_text, &queries, 0.9, true);
+        assert_eq!(by_position.len(), 2);
+        assert_eq!(by_position[0].0, "first phrase");
+        assert_eq!(by_position[0].1.start_index, 0);
+        assert_eq!(by_position[1].0, "second phrase");
+    }
+
+    #[test]
+    fn test_max_extra_words_recovers_interspersed_match() {
+        let embedded_text = "[[PARAGRAPH]] intro total 1 revenue 2 for 3 the 4 5 year end";
+        let search_string = "total revenue for the year";
+        
+        let default_result = find_closest_match(embedded_text, search_string).unwrap();
+        assert_eq!(default_result.similarity, 0.2);
+        
+        let mut options = MatchOptions::new();
+        options.max_extra_words = Some(5);
+        let wide = find_closest_match_with_options(embedded_text, search_string, &options).unwrap();
+        assert_eq!(wide.text, "total 1 revenue 2 for 3 the 4 5 year");
+        assert_eq!((wide.start_index, wide.end_index), (1, 11));
+        assert!(wide.similarity > 0.9);
+    }
+
+    #[test]
+    fn test_lcs_word_similarity_tolerates_dropped_words() {
+        let ocr = vec!["the", "quick", "brown", "fox"];
+        let search = vec!["the", "brown", "fox"];
+        
+        assert_eq!(sequence_similarity(&ocr, &search), 0.25);
+        assert_eq!(lcs_word_similarity(&ocr, &search), 0.75);
+        assert_eq!(lcs_word_similarity(&ocr, &ocr), 1.0);
+    }
+
+    #[test]
+    fn test_classify_match_confidence() {
+        assert_eq!(classify_match_confidence(1.0, None, None), "high");
+        assert_eq!(classify_match_confidence(0.9, None, None), "high");
+        assert_eq!(classify_match_confidence(0.89, None, None), "medium");
+        assert_eq!(classify_match_confidence(0.7, None, None), "medium");
+        assert_eq!(classify_match_confidence(0.69, None, None), "low");
+        assert_eq!(classify_match_confidence(0.0, None, None), "low");
+        
+        // Custom thresholds
+        assert_eq!(classify_match_confidence(0.85, Some(0.8), Some(0.5)), "high");
+        assert_eq!(classify_match_confidence(0.5, Some(0.8), Some(0.5)), "medium");
+    }
+
+    #[test]
+    fn test_alias_counts_as_exact_match() {
+        let embedded_text = "[[PARAGRAPH]] 221B Baker Street London";
+        
+        let plain = find_closest_match(embedded_text, "Baker St").unwrap();
+        assert!(plain.similarity < 1.0);
+        
+        let mut options = MatchOptions::new();
+        options.add_alias("St", "Street");
+        let aliased = find_closest_match_with_options(embedded_text, "Baker St", &options).unwrap();
+        assert_eq!(aliased.similarity, 1.0);
+        assert_eq!(aliased.text, "Baker Street");
+    }
+
+    #[test]
+    fn test_unrelated_alias_keeps_case_sensitivity() {
+        let embedded_text = "[[PARAGRAPH]] say hello world today";
+        let plain = find_closest_match(embedded_text, "Hello World").unwrap();
+        
+        let mut options = MatchOptions::new();
+        options.add_alias("foo", "bar");
+        let aliased = find_closest_match_with_options(embedded_text, "Hello World", &options).unwrap();
+        assert!(aliased.similarity < 1.0);
+        assert_eq!(aliased.similarity, plain.similarity);
+    }
+
+    #[test]
+    fn test_flexible_hyphens_matches_all_renderings() {
+        let mut options = MatchOptions::new();
+        options.flexible_hyphens = true;
+        
+        for (ocr, expected) in [
+            ("[[PARAGRAPH]] please send e-mail today", "send e-mail today"),
+            ("[[PARAGRAPH]] please send e mail today", "send e mail today"),
+            ("[[PARAGRAPH]] please send email today", "send email today"),
+        ] {
+            let result = find_closest_match_with_options(ocr, "send e-mail today", &options).unwrap();
+            assert_eq!(result.similarity, 1.0, "{}", ocr);
+            assert_eq!(result.text, expected);
+        }
+        
+        let strict = find_closest_match("[[PARAGRAPH]] please send e mail today", "send e-mail today").unwrap();
+        assert!(strict.similarity < 1.0);
+        assert_eq!(hyphen_variants("a-b c"), vec!["a-b c", "ab c", "a b c"]);
+    }
+
+    #[test]
+    fn test_length_weighted_similarity_discounts_short_words() {
+        let search = ["a", "quick", "of", "brown"];
+        let function_words = ["a", "slow", "of", "green"];
+        let content_words = ["an", "quick", "on", "brown"];
+        
+        assert_eq!(sequence_similarity(&function_words, &search), sequence_similarity(&content_words, &search));
+        assert!(length_weighted_similarity(&function_words, &search) < length_weighted_similarity(&content_words, &search));
+        assert_eq!(length_weighted_similarity(&search, &search), 1.0);
+        
+        let mut options = MatchOptions::new();
+        options.metric = SimilarityMetric::LengthWeighted;
+        assert_eq!(window_similarity(&content_words, &search, &options), length_weighted_similarity(&content_words, &search));
+    }
+
+    #[test]
+    fn test_string_similarity_metrics() {
+        assert!((string_similarity("kitten", "sitting", "levenshtein").unwrap() - (1.0 - 3.0 / 7.0)).abs() < 1e-9);
+        assert!((string_similarity("MARTHA", "MARHTA", "jaro_winkler").unwrap() - 0.9611).abs() < 1e-4);
+        assert!((string_similarity("DIXON", "DICKSONX", "Jaro-Winkler").unwrap() - 0.8133).abs() < 1e-4);
+        assert!((string_similarity("abcd", "abce", "trigram").unwrap() - 1.0 / 3.0).abs() < 1e-9);
+        
+        for metric in ["levenshtein", "jaro_winkler", "trigram"] {
+            assert_eq!(string_similarity("same", "same", metric), Some(1.0));
+            assert_eq!(string_similarity("", "", metric), Some(1.0));
+        }
+        assert_eq!(string_similarity("a", "b", "soundex"), None);
+    }
+
+    #[test]
+    fn test_whole_word_only_rejects_partial_tokens() {
+        let embedded_text = "[[PARAGRAPH]] order 12345 shipped";
+        
+        let partial = find_closest_match(embedded_text, "order 123").unwrap();
+        assert_eq!(partial.text, "order 12345");
+        
+        let mut options = MatchOptions::new();
+        options.whole_word_only = true;
+        assert!(find_closest_match_with_options(embedded_text, "123", &options).is_none());
+        
+        // Only "order" counts; no character-overlap credit for "12345"
+        let phrase = find_closest_match_with_options(embedded_text, "order 123", &options).unwrap();
+        assert_eq!(phrase.similarity, 0.5);
+        
+        let standalone = find_closest_match_with_options("[[PARAGRAPH]] order 123 shipped", "123", &options).unwrap();
+        assert_eq!((standalone.text.as_str(), standalone.similarity), ("123", 1.0));
+    }
+
+    #[test]
+    fn test_grapheme_aware_similarity() {
+        let decomposed = "cafe\u{301}";
+        let precomposed = "caf\u{e9}";
+        assert_eq!(calculate_text_similarity(decomposed, precomposed), 1.0);
+        assert_eq!(calculate_word_similarity(decomposed, "cafx", &MatchOptions::new()), 0.75);
+        
+        assert_eq!(grapheme_clusters("\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}").len(), 1);
+        assert_eq!(grapheme_clusters("a\u{1F44D}\u{1F3FD}").len(), 2);
+        assert_eq!(grapheme_clusters("\u{1F1FA}\u{1F1F8}\u{1F1EB}\u{1F1F7}").len(), 2);
+        assert_eq!(grapheme_clusters("\u{1100}\u{1161}\u{11A8}"), vec!["\u{AC01}"]);
+        assert_eq!(calculate_text_similarity("\u{c5}ngstr\u{f6}m", "A\u{30a}ngstro\u{308}m"), 1.0);
+        assert_eq!(calculate_text_similarity("a\u{1F44D}\u{1F3FD}", "a\u{1F44D}"), 0.5);
+    }
+
+    #[test]
+    fn test_drop_numeric_tokens_bridges_page_number() {
+        let embedded_text = "[[PARAGRAPH]] the quick brown 17 fox jumps over";
+        
+        let plain = find_closest_match(embedded_text, "quick brown fox jumps").unwrap();
+        assert!(plain.similarity < 0.8);
+        
+        let mut options = MatchOptions::new();
+        options.drop_numeric_tokens = true;
+        let result = find_closest_match_with_options(embedded_text, "quick brown fox jumps", &options).unwrap();
+        assert_eq!(result.similarity, 1.0);
+        assert_eq!((result.start_index, result.end_index), (1, 6));
+        assert_eq!(result.text, "quick brown 17 fox jumps");
+    }
+
+    #[test]
+    fn test_fuzzy_tiebreak_prefers_search_length_then_earliest() {
+        let search_words = ["ab", "cd", "ab"];
+        let options = MatchOptions::new();
+        
+        // [ab cd] (2 words) and [ab cd x] (3 words) both score 0.666
+        let cleaned_words = ["ab", "ab", "cd", "x"];
+        let result = find_fuzzy_match(&cleaned_words, &search_words, &options, "", "", 0, 0).unwrap();
+        assert_eq!(result.similarity, 0.666);
+        assert_eq!((result.start_index, result.end_index), (1, 4));
+        
+        // Same size and score twice: the earlier window wins
+        let cleaned_words = ["ab", "cd", "x", "ab", "cd", "x"];
+        let result = find_fuzzy_match(&cleaned_words, &search_words, &options, "", "", 0, 0).unwrap();
+        assert_eq!((result.start_index, result.end_index), (0, 3));
+    }
+
+    #[test]
+    fn test_fuzzy_accept_threshold_and_substring_credit() {
+        let embedded_text = "[[PARAGRAPH]] order 12345 shipped";
+        
+        let marginal = find_closest_match(embedded_text, "order 123").unwrap();
+        assert_eq!(marginal.similarity, 0.9);
+        
+        let mut strict = MatchOptions::new();
+        strict.fuzzy_accept_threshold = 0.95;
+        let result = find_closest_match_with_options(embedded_text, "order 123", &strict).unwrap();
+        assert_eq!(result.similarity, 0.5); // only the exact pass remains
+        
+        let mut low_credit = MatchOptions::new();
+        low_credit.substring_credit = 0.5;
+        let result = find_closest_match_with_options(embedded_text, "order 123", &low_credit).unwrap();
+        assert!(result.similarity < 0.9);
+    }
+
+    #[test]
+    fn test_normalize_punctuation_variants() {
+        let embedded_text = "[[PARAGRAPH]] he said \"it's 1990-1995\" again";
+        let search = "said \u{201C}it\u{2019}s 1990\u{2013}1995\u{201D}";
+        
+        let plain = find_closest_match(embedded_text, search).unwrap();
+        assert!(plain.similarity < 1.0);
+        
+        let mut options = MatchOptions::new();
+        options.normalize_punctuation_variants = true;
+        let result = find_closest_match_with_options(embedded_text, search, &options).unwrap();
+        assert_eq!(result.similarity, 1.0);
+        assert_eq!(result.text, "said \"it's 1990-1995\"");
+    }
+
+    #[test]
+    fn test_matched_word_and_char_counts() {
+        let result = find_closest_match("[[PARAGRAPH]] see the café menu today", "the café menu").unwrap();
+        assert_eq!(result.text, "the café menu");
+        assert_eq!(result.matched_word_count(), 3);
+        assert_eq!(result.matched_char_count(), 13);
+    }
+
+    #[test]
+    fn test_preview_match_brackets_matched_words() {
+        let embedded_text = "[[PARAGRAPH]] [[LINE 0 0 100 10]] we saw the quick brown fox jump over the dog";
+        
+        assert_eq!(
+            preview_match(embedded_text, "quick brown fox", 2).unwrap(),
+            "... saw the [quick brown fox] jump over ..."
+        );
+        assert_eq!(
+            preview_match(embedded_text, "we saw", 1).unwrap(),
+            "[we saw] the ..."
+        );
+        assert_eq!(preview_match(embedded_text, "the dog", 5).unwrap(), "... quick brown fox jump over [the dog]");
+    }
+
+    #[test]
+    fn test_best_of_candidates_prefers_higher_score() {
+        let embedded_text = "[[PARAGRAPH]] filed by Acme Corp. on behalf of";
+        let candidates: Vec<String> = ["Acme Corporation Inc.", "Acme Corp."].iter().map(|s| s.to_string()).collect();
+        
+        let (candidate, result) = best_of_candidates(embedded_text, &candidates).unwrap();
+        assert_eq!(candidate, "Acme Corp.");
+        assert_eq!(result.text, "Acme Corp.");
+        assert_eq!(result.similarity, 1.0);
+        assert_eq!((result.start_index, result.end_index), (2, 4));
+        assert!(best_of_candidates(embedded_text, &[]).is_none());
+    }
+
+    #[test]
+    fn test_normalize_markers() {
+        let irregular = "[[ paragraph ]]\t[[LINE\t10  20 30 40 ]]hello world [[ Line 1 2 3 4]]  again";
+        
+        assert_eq!(
+            normalize_markers(irregular),
+            "[[PARAGRAPH]] [[LINE 10 20 30 40]] hello world [[LINE 1 2 3 4]] again"
+        );
+        assert_eq!(clean_embedded_text(&normalize_markers(irregular)), "hello world again");
+        
+        let result = find_closest_match(irregular, "world again").unwrap();
+        assert_eq!((result.text.as_str(), result.similarity), ("world again", 1.0));
+    }
+
+    #[test]
+    fn test_aggregation_min_gates_on_worst_word() {
+        let embedded_text = "[[PARAGRAPH]] invoice number xq total";
+        let search = "invoice number date total";
+        
+        let mut options = MatchOptions::new();
+        let mean = find_closest_match_with_options(embedded_text, search, &options).unwrap();
+        assert!(mean.similarity >= 0.75);
+        
+        options.aggregation = Aggregation::Min;
+        assert!(find_closest_match_with_options(embedded_text, search, &options).is_none());
+        
+        // A near-miss on every word still passes when none is bad
+        let typo = find_closest_match_with_options("[[PARAGRAPH]] invoice numbr date total", search, &options).unwrap();
+        assert_eq!(typo.text, "invoice numbr date total");
+        assert!(typo.similarity > 0.75);
+        
+        assert!((aggregate_similarities(&[1.0, 0.5], Aggregation::Harmonic) - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fuzzy_match_reports_unmatched_search_words() {
+        let embedded = "please pay the invoice total before friday";
+        let result = find_closest_match(embedded, "pay the invoice zzqv before").unwrap();
+        
+        assert!(result.similarity < 1.0);
+        assert_eq!(result.unmatched_search_words, vec![3]);
+        
+        let exact = find_closest_match(embedded, "invoice total").unwrap();
+        assert!(exact.unmatched_search_words.is_empty());
+    }
+
+    #[test]
+    fn test_contains_match_stops_at_first_qualifying_window() {
+        let mut embedded = String::from("[[LINE 0 0 100 10]] the quick brown cat ");
+        embedded.push_str(&"filler words ".repeat(500));
+        embedded.push_str("the quick brown fox");
+        
+        assert!(contains_match(&embedded, "the quick brown fox", 0.7));
+        // The near match at the start qualifies before the exact one at the end is reached
+        assert_eq!(first_window_at_or_above(&embedded, "the quick brown fox", 0.7), Some(0));
+        assert_eq!(first_window_at_or_above(&embedded, "the quick brown fox", 1.0), Some(1004));
+        assert!(!contains_match(&embedded, "lazy dog", 0.5));
+    }
+
+    #[test]
+    fn test_contains_match_agrees_with_fuzzy_pass() {
+        let embedded = "[[PARAGRAPH]] order 12345 shipped";
+        let similarity = find_closest_match(embedded, "order 123").unwrap().similarity;
+        
+        assert!(first_window_at_or_above(embedded, "order 123", 0.8).is_none());
+        assert!(contains_match(embedded, "order 123", 0.8));
+        assert!(contains_match(embedded, "order 123", similarity));
+        assert!(!contains_match(embedded, "order 123", similarity + 0.01));
+    }
+
+    #[test]
+    fn test_normalize_number_grouping_matches_all_renderings() {
+        let mut options = MatchOptions::new();
+        options.normalize_number_grouping = true;
+        let renderings = ["1,000,000", "1000000", "1 000 000"];
+        
+        for ocr in renderings {
+            let embedded = format!("[[LINE 10 10 400 40]] total of {} dollars ", ocr);
+            for search in renderings {
+                let query = format!("total of {} dollars", search);
+                let result = find_closest_match_with_options(&embedded, &query, &options).unwrap();
+                assert_eq!(result.similarity, 1.0, "{} vs {}", ocr, search);
+                assert_eq!(result.text(), format!("total of {} dollars", ocr));
+                assert_eq!(result.start_index, 0);
+            }
+        }
+        
+        let strict = find_closest_match("total of 1 000 000 dollars", "total of 1,000,000 dollars").unwrap();
+        assert!(strict.similarity < 1.0);
+    }
+
+    #[test]
+    fn test_search_longer_than_text_falls_back_to_whole_text() {
+        let embedded = "[[LINE 0 0 900 30]] this agreement is made between the buyer and the seller ";
+        let search = "this agreement is made between the buyer and the seller effective immediately";
+        assert_eq!(search.split_whitespace().count(), 12);
+        
+        let result = find_closest_match(embedded, search).unwrap();
+        assert_eq!((result.start_index, result.end_index), (0, 10));
+        assert!(result.similarity > 0.8 && result.similarity < 1.0);
+        assert!(result.unmatched_search_words.contains(&10));
+        
+        assert!(find_closest_match(embedded, "completely unrelated words that share nothing at all with the text here").is_none());
+    }
+
+    #[test]
+    fn test_affix_overlap_credit_scores_truncation_by_length() {
+        let mut options = MatchOptions::new();
+        assert_eq!(calculate_word_similarity("dep", "department", &options), DEFAULT_SUBSTRING_CREDIT);
+        
+        options.affix_overlap_credit = true;
+        assert!((calculate_word_similarity("departme", "department", &options) - 0.8).abs() < 1e-9);
+        assert!((calculate_word_similarity("department", "dep", &options) - 0.3).abs() < 1e-9);
+        assert!((calculate_word_similarity("ment", "department", &options) - 0.4).abs() < 1e-9);
+        // Containment in the middle keeps the flat credit
+        assert_eq!(calculate_word_similarity("part", "department", &options), DEFAULT_SUBSTRING_CREDIT);
+    }
+
+    #[test]
+    fn test_best_match_excluding_skips_window_near_excluded_term() {
+        let embedded = "[[LINE 0 0 500 20]] card payment declined by bank [[LINE 0 30 500 50]] \
+            then a later card payment was received in full ";
+        let exclude = vec!["Declined".to_string()];
+        
+        let plain = find_closest_match(embedded, "card payment").unwrap();
+        assert_eq!(plain.start_index, 0);
+        
+        let result = best_match_excluding(embedded, "card payment", &exclude).unwrap();
+        assert_eq!((result.start_index, result.end_index), (8, 10));
+        assert_eq!(result.text(), "card payment");
+        
+        assert!(best_match_excluding("payment declined", "payment", &exclude).is_none());
+        
+        // Windows are scored like find_closest_match, so OCR typos still match
+        let typo = best_match_excluding("[[PARAGRAPH]] the invoise totl was paid", "invoice total", &exclude).unwrap();
+        assert_eq!(typo.text(), "invoise totl");
+    }
+
+    #[test]
+    fn test_length_penalty_prefers_same_length_window() {
+        let embedded = "[[LINE 0 0 500 20]] ref ab12 zz999 then ref ab12 zz99 ";
+        
+        let plain = window_similarity(&["ab12", "zz999"], &["ab12", "cd34"], &MatchOptions::new());
+        assert_eq!(plain, window_similarity(&["ab12", "zz99"], &["ab12", "cd34"], &MatchOptions::new()));
+        
+        let mut options = MatchOptions::new();
+        options.length_penalty = true;
+        let longer = window_similarity(&["ab12", "zz999"], &["ab12", "cd34"], &options);
+        let same = window_similarity(&["ab12", "zz99"], &["ab12", "cd34"], &options);
+        assert!((longer - 0.45).abs() < 1e-9);
+        assert_eq!(same, 0.5);
+        
+        assert_eq!(find_closest_match(embedded, "ab12 cd34").unwrap().start_index, 1);
+        let result = find_closest_match_with_options(embedded, "ab12 cd34", &options).unwrap();
+        assert_eq!((result.start_index, result.text()), (5, "ab12 zz99".to_string()));
+    }
+
+    #[test]
+    fn test_similarity_at_index_matches_full_search() {
+        let embedded = "[[PARAGRAPH]] [[LINE 0 0 500 20]] the invoice total was paid in full by the customer ";
+        let result = find_closest_match(embedded, "total was paid in part").unwrap();
+        
+        assert_eq!(similarity_at_index(embedded, "total was paid in part", result.start_index), Some(result.similarity));
+        assert_eq!(similarity_at_index(embedded, "invoice total", 1), Some(1.0));
+        assert_eq!(similarity_at_index(embedded, "invoice total", 0), Some(0.0));
+        assert_eq!(similarity_at_index(embedded, "invoice total", 9), None);
+    }
+
+    #[test]
+    fn test_require_prefix_changes_selected_window() {
+        let embedded = "[[LINE 0 0 500 20]] order number 4471 due [[LINE 0 30 500 50]] invoice number 9020 due ";
+        
+        let plain = find_closest_match(embedded, "invoice number 4471 due").unwrap();
+        assert_eq!(plain.start_index, 0);
+        
+        let mut options = MatchOptions::new();
+        options.set_require_prefix(Some("Invoice".to_string()));
+        let result = find_closest_match_with_options(embedded, "invoice number 4471 due", &options).unwrap();
+        assert_eq!(result.start_index, 4);
+        assert_eq!(result.text(), "invoice number 9020 due");
+        
+        options.set_require_prefix(Some("receipt".to_string()));
+        assert!(find_closest_match_with_options(embedded, "invoice number 4471 due", &options).is_none());
+    }
+
+    #[test]
+    fn test_respect_line_boundaries_blocks_joining_broken_word() {
+        let embedded = "[[LINE 0 0 500 20]] please contact our inter- [[LINE 0 30 500 50]] national support desk ";
+        let search = "contact our international support";
+        
+        let strict = find_closest_match(embedded, search).unwrap();
+        assert!(strict.similarity < 1.0);
+        
+        let mut options = MatchOptions::new();
+        options.respect_line_boundaries = false;
+        let result = find_closest_match_with_options(embedded, search, &options).unwrap();
+        assert_eq!(result.similarity, 1.0);
+        assert_eq!((result.start_index, result.end_index), (1, 6));
+        assert_eq!(result.text(), "contact our inter- national support");
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    #[wasm_bindgen_test::wasm_bindgen_test]
+    fn test_find_multiple_matches_round_trips_into_entries() {
+        let embedded_text = "[[LINE 0 0 100 20]] the quick brown fox jumps ";
+        let output = find_multiple_matches(embedded_text, &js_sys::Array::of1(&"brown fox".into()), 0.9, false);
+        
+        // The object shape the TypeScript side reads
+        let first = output.get(0);
+        for key in ["text", "similarity", "startIndex", "endIndex", "searchQuery"] {
+            assert!(js_sys::Reflect::has(&first, &key.into()).unwrap(), "{}", key);
+        }
+        
+        let entries: Vec<MultipleMatchEntry> = serde_wasm_bindgen::from_value(output.into()).unwrap();
+        assert_eq!(entries, vec![MultipleMatchEntry {
+            text: "brown fox".to_string(),
+            similarity: 1.0,
+            start_index: 2,
+            end_index: 4,
+            search_query: "brown fox".to_string(),
+        }]);
+    }
+
+    #[test]
+    fn test_fuzzy_match_reports_weakest_word() {
+        let embedded = "[[LINE 0 0 500 20]] the quarterly revenue reprot shows xylophone growth overall ";
+        let result = find_closest_match(embedded, "quarterly revenue report shows strong growth").unwrap();
+        
+        let weakest = result.weakest_word.unwrap();
+        assert_eq!(weakest.word, "xylophone");
+        assert_eq!(weakest.position, 4);
+        assert!(weakest.similarity < 0.5);
+        
+        let exact = find_closest_match(embedded, "revenue reprot shows").unwrap();
+        assert!(exact.weakest_word.is_none());
+    }
+
+    #[test]
+    fn test_rejoin_split_words_matches_spaced_out_word() {
+        let embedded = "[[LINE 0 0 500 20]] please sign and return this signed docu ment today ";
+        let search = "please sign and return this signed document";
+        
+        let plain = find_closest_match(embedded, search).unwrap();
+        assert!(plain.similarity < 1.0);
+        assert_eq!(plain.text(), "please sign and return this signed docu");
+        
+        let mut options = MatchOptions::new();
+        options.rejoin_split_words = true;
+        let result = find_closest_match_with_options(embedded, search, &options).unwrap();
+        assert_eq!(result.similarity, 1.0);
+        assert_eq!((result.start_index, result.end_index), (0, 8));
+        assert_eq!(result.text(), "please sign and return this signed docu ment");
+        
+        // A pair that only equals the search word ignoring case is left alone
+        let (words, _) = rejoin_split_search_words(
+            &["Docu".to_string(), "ment".to_string(), "docu".to_string(), "ment".to_string()],
+            "document",
+            &options,
+        );
+        assert_eq!(words, vec!["Docu", "ment", "document"]);
+    }
+
+    #[test]
+    fn test_window_size_reports_words_spanned() {
+        let embedded = "[[LINE 0 0 500 20]] please sign this docu ment today ";
+        
+        // The fuzzy pass settles on a window one word longer than the search
+        let result = find_closest_match(embedded, "sign this document").unwrap();
+        assert_eq!(result.debug_search_word_count, 3);
+        assert_eq!(result.window_size, 4);
+        assert_eq!(result.window_size, result.end_index - result.start_index);
+        
+        let exact = find_closest_match(embedded, "please sign").unwrap();
+        assert_eq!(exact.window_size, 2);
+    }
+
+    #[test]
+    fn test_strip_number_grouping_only_touches_grouped_numbers() {
+        assert_eq!(strip_number_grouping("1,000,000"), "1000000");
+        assert_eq!(strip_number_grouping("12'345"), "12345");
+        assert_eq!(strip_number_grouping("1\u{202F}000"), "1000");
+        for unchanged in ["1234,567", "1,00", "1,,000", "a,000", "1000", ",000"] {
+            assert_eq!(strip_number_grouping(unchanged), unchanged);
+        }
+    }
 }
\ No newline at end of file
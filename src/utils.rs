@@ -3,12 +3,21 @@ use wasm_bindgen::prelude::*;
 // A macro to provide `println!(..)`-style syntax for `console.log` logging.
 macro_rules! log {
     ( $( $t:tt )* ) => {
-        web_sys::console::log_1(&format!( $( $t )* ).into());
+        $crate::utils::console_log(&format!( $( $t )* ))
     }
 }
 
 pub(crate) use log;
 
+/// Write a message to the browser console. Native builds (unit tests) have no
+/// console to call into, so the message is dropped there.
+pub(crate) fn console_log(message: &str) {
+    #[cfg(target_arch = "wasm32")]
+    web_sys::console::log_1(&message.into());
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = message;
+}
+
 #[wasm_bindgen]
 pub fn set_panic_hook() {
     // When the `console_error_panic_hook` feature is enabled, we can call the